@@ -0,0 +1,103 @@
+use crate::RewriteError;
+use crate::offset_based_rewriter::{Interface, OffsetBasedRewriter};
+use std::ops::Range;
+
+/// Like [`Rewriter`], but rewrites bytes rather than a `str`, and takes byte ranges rather than
+/// spans
+///
+/// A `ByteRewriter` can rewrite text that is not valid UTF-8. As with a `Rewriter`, the ranges
+/// passed to [`ByteRewriter::rewrite`] must be in order and must not overlap.
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Clone, Debug)]
+pub struct ByteRewriter<'original> {
+    original: &'original [u8],
+    /// The end of the most recently rewritten range
+    offset: usize,
+    offset_based_rewriter: OffsetBasedRewriter<[u8]>,
+}
+
+impl<'original> ByteRewriter<'original> {
+    #[must_use]
+    pub fn new(original: &'original [u8]) -> Self {
+        Self {
+            original,
+            offset: 0,
+            offset_based_rewriter: OffsetBasedRewriter::new(original),
+        }
+    }
+
+    #[must_use]
+    pub fn contents(self) -> Vec<u8> {
+        self.offset_based_rewriter.contents(self.original)
+    }
+
+    /// Returns the contents as they would be if [`ByteRewriter::contents`] were called now,
+    /// without consuming the `ByteRewriter`
+    #[must_use]
+    pub fn preview(&self) -> Vec<u8> {
+        self.offset_based_rewriter.preview(self.original)
+    }
+
+    /// Replaces `range` of the original bytes with `replacement`, and returns the replaced bytes
+    ///
+    /// A range that extends past the end of the original bytes is clamped to the end. A range that
+    /// starts past the end is not rewritten, as with `Rewriter`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` starts before the end of the previously rewritten range, or if `range` is
+    /// reversed.
+    pub fn rewrite(&mut self, range: Range<usize>, replacement: &[u8]) -> Vec<u8> {
+        assert!(
+            self.offset <= range.start && range.start <= range.end,
+            "self.offset = {}, range = {range:?}",
+            self.offset,
+        );
+
+        self.offset = range.end;
+
+        if self.original.len() < range.start {
+            return Vec::new();
+        }
+
+        let end = range.end.min(self.original.len());
+
+        self.offset_based_rewriter
+            .rewrite(self.original, range.start, end, replacement)
+    }
+
+    /// Like [`ByteRewriter::rewrite`], but returns an error rather than panicking or clamping when
+    /// `range` is out of order, reversed, or not within the original bytes
+    ///
+    /// The errors' "line-columns" are byte offsets. When an error is returned, no rewrite is
+    /// performed and the `ByteRewriter` can continue to be used.
+    pub fn try_rewrite(
+        &mut self,
+        range: Range<usize>,
+        replacement: &[u8],
+    ) -> Result<Vec<u8>, RewriteError<usize>> {
+        if range.start < self.offset {
+            return Err(RewriteError::OutOfOrder {
+                previous_end: self.offset,
+                start: range.start,
+            });
+        }
+
+        if range.end < range.start {
+            return Err(RewriteError::ReversedSpan {
+                start: range.start,
+                end: range.end,
+            });
+        }
+
+        if self.original.len() < range.end {
+            return Err(RewriteError::PastEndOfFile {
+                line_column: range.end,
+                end_of_file: self.original.len(),
+            });
+        }
+
+        Ok(self.rewrite(range, replacement))
+    }
+}
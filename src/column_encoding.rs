@@ -0,0 +1,135 @@
+use std::num::NonZeroUsize;
+
+#[cfg(feature = "graphemes")]
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The unit in which a [`LineColumn`]'s column is counted
+///
+/// A column that falls inside a character (e.g., the second byte of a two-byte character, or the
+/// second code unit of a surrogate pair) is rounded down to the start of the character.
+///
+/// [`LineColumn`]: crate::interface::LineColumn
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ColumnEncoding {
+    /// Unicode scalar values, as in [`Span`](crate::Span) and `proc_macro2::LineColumn`
+    #[default]
+    Chars,
+    /// UTF-8 bytes
+    Bytes,
+    /// UTF-16 code units, as in the Language Server Protocol's default position encoding
+    Utf16,
+    /// Extended grapheme clusters, as displayed by most editors
+    #[cfg(feature = "graphemes")]
+    Graphemes,
+    /// Display columns, as in terminal output and `rustc`'s rendered diagnostics
+    ///
+    /// A tab advances to the next multiple of `tab_width`. Every other character is one column. A
+    /// column that falls inside a tab's expansion is rounded down to the start of the tab.
+    Visual { tab_width: NonZeroUsize },
+}
+
+impl ColumnEncoding {
+    /// Advances through `line` by up to `n` columns
+    ///
+    /// `start_column` is the column at which `line` starts, which matters only for tab stops.
+    ///
+    /// Returns the number of bytes advanced, whether they were all ASCII, and the number of columns
+    /// advanced. The last of these is less than `n` if `line` has fewer than `n` columns, or if
+    /// the `n`th column falls inside a character. The two cases can be distinguished by whether
+    /// all of `line` was advanced through.
+    #[allow(clippy::disallowed_methods)]
+    pub(crate) fn advance(self, line: &str, start_column: usize, n: usize) -> (usize, bool, usize) {
+        let mut offset = 0;
+        let mut ascii = true;
+        let mut count = 0;
+        match self {
+            Self::Chars => {
+                for c in line.chars().take(n) {
+                    offset += c.len_utf8();
+                    ascii &= c.is_ascii();
+                    count += 1;
+                }
+            }
+            Self::Bytes => {
+                offset = n.min(line.len());
+                while !line.is_char_boundary(offset) {
+                    offset -= 1;
+                }
+                ascii = line.as_bytes()[..offset].is_ascii();
+                count = offset;
+            }
+            Self::Utf16 => {
+                for c in line.chars() {
+                    if n < count + c.len_utf16() {
+                        break;
+                    }
+                    offset += c.len_utf8();
+                    ascii &= c.is_ascii();
+                    count += c.len_utf16();
+                }
+            }
+            #[cfg(feature = "graphemes")]
+            Self::Graphemes => {
+                for grapheme in line.graphemes(true).take(n) {
+                    offset += grapheme.len();
+                    ascii &= grapheme.is_ascii();
+                    count += 1;
+                }
+            }
+            Self::Visual { tab_width } => {
+                let mut column = start_column;
+                for c in line.chars() {
+                    let next = next_visual_column(column, c, tab_width);
+                    if start_column + n < next {
+                        break;
+                    }
+                    offset += c.len_utf8();
+                    ascii &= c.is_ascii();
+                    column = next;
+                }
+                count = column - start_column;
+            }
+        }
+        (offset, ascii, count)
+    }
+
+    /// Returns the number of columns in `line` before `offset`
+    ///
+    /// If `offset` falls inside a character, the character is not counted.
+    #[allow(clippy::disallowed_methods)]
+    pub(crate) fn columns(self, line: &str, offset: usize) -> usize {
+        let mut offset = offset;
+        while !line.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let prefix = &line[..offset];
+        match self {
+            Self::Chars => prefix.chars().count(),
+            Self::Bytes => prefix.len(),
+            Self::Utf16 => prefix.chars().map(char::len_utf16).sum(),
+            #[cfg(feature = "graphemes")]
+            Self::Graphemes => line
+                .grapheme_indices(true)
+                .take_while(|(index, grapheme)| index + grapheme.len() <= offset)
+                .count(),
+            Self::Visual { tab_width } => prefix
+                .chars()
+                .fold(0, |column, c| next_visual_column(column, c, tab_width)),
+        }
+    }
+
+    /// Returns whether a tab can be more than one column
+    ///
+    /// In every encoding, each ASCII character other than a tab is one column.
+    pub(crate) fn expands_tabs(self) -> bool {
+        matches!(self, Self::Visual { .. })
+    }
+}
+
+fn next_visual_column(column: usize, c: char, tab_width: NonZeroUsize) -> usize {
+    if c == '\t' {
+        (column / tab_width + 1) * tab_width.get()
+    } else {
+        column + 1
+    }
+}
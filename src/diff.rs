@@ -0,0 +1,289 @@
+//! Unified diffs
+//!
+//! A [`Diff`] is produced by [`Rewriter::diff`] from the edits the `Rewriter` has recorded. Its
+//! [`Display`] implementation emits a unified diff that can be applied with `git apply` or
+//! `patch -p1`.
+//!
+//! Lines are split as the `Rewriter`'s [`TextFormat`] splits them, so hunk headers agree with the
+//! `Rewriter`'s line numbers. A line that does not end with `\n` is followed by a
+//! `\ No newline at end of file` marker, so diffs of text with other line terminators (e.g., a
+//! lone `\r`) may not be applicable by those tools.
+//!
+//! [`Rewriter::diff`]: crate::Rewriter::diff
+
+use crate::source_map::Edit;
+use crate::{LineIndex, TextFormat};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diff {
+    path_label: String,
+    hunks: Vec<Hunk>,
+}
+
+/// A hunk of a unified diff
+///
+/// Line numbers are 1-based, as in a hunk header. As in a hunk header, a start refers to the line
+/// before the hunk when the corresponding length is 0.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Hunk {
+    pub original_start: usize,
+    pub original_len: usize,
+    pub rewritten_start: usize,
+    pub rewritten_len: usize,
+    pub lines: Vec<Line>,
+}
+
+/// A line of a [`Hunk`]
+///
+/// Each line's text includes its terminating newline, if any.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Line {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+impl Diff {
+    pub(crate) fn new(
+        path_label: &str,
+        original: &str,
+        rewritten: &str,
+        edits: &[Edit],
+        context: usize,
+        format: TextFormat,
+    ) -> Self {
+        let original = Lines::new(original, format);
+        let rewritten = Lines::new(rewritten, format);
+        let blocks = blocks(&original, &rewritten, edits);
+        let hunks = hunks(&original, &rewritten, &blocks, context);
+        Self {
+            path_label: path_label.to_owned(),
+            hunks,
+        }
+    }
+
+    #[must_use]
+    pub fn path_label(&self) -> &str {
+        &self.path_label
+    }
+
+    #[must_use]
+    pub fn hunks(&self) -> &[Hunk] {
+        &self.hunks
+    }
+
+    /// Returns true if the diff has no hunks, i.e., the rewritten text is unchanged
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.hunks.is_empty() {
+            return Ok(());
+        }
+        writeln!(f, "--- a/{}", self.path_label)?;
+        writeln!(f, "+++ b/{}", self.path_label)?;
+        for hunk in &self.hunks {
+            write!(f, "{hunk}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Hunk {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "@@ -{} +{} @@",
+            range(self.original_start, self.original_len),
+            range(self.rewritten_start, self.rewritten_len)
+        )?;
+        for line in &self.lines {
+            let (prefix, text) = match line {
+                Line::Context(text) => (' ', text),
+                Line::Removed(text) => ('-', text),
+                Line::Added(text) => ('+', text),
+            };
+            write!(f, "{prefix}{text}")?;
+            if !text.ends_with('\n') {
+                writeln!(f)?;
+                writeln!(f, "\\ No newline at end of file")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn range(start: usize, len: usize) -> String {
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{start},{len}")
+    }
+}
+
+/// A text's lines, as split by a [`LineIndex`]
+struct Lines<'a> {
+    line_index: LineIndex,
+    /// The text of each line, including its terminator
+    ///
+    /// Unlike `line_index`, `lines` has no empty last line when the text is empty or ends with a
+    /// line terminator.
+    lines: Vec<&'a str>,
+}
+
+impl<'a> Lines<'a> {
+    fn new(text: &'a str, format: TextFormat) -> Self {
+        let line_index = LineIndex::with_format(text, format);
+        let starts = (1..=line_index.line_count())
+            .filter_map(|line| line_index.line_range(line))
+            .map(|range| range.start)
+            .collect::<Vec<_>>();
+        let lines = starts
+            .iter()
+            .zip(starts.iter().skip(1).chain(std::iter::once(&text.len())))
+            .map(|(&start, &end)| &text[start..end])
+            .filter(|line| !line.is_empty())
+            .collect();
+        Self { line_index, lines }
+    }
+
+    /// Returns the 0-based index of the line containing `offset`
+    fn line(&self, offset: usize) -> usize {
+        self.line_index.line_from_offset(offset).unwrap() - 1
+    }
+
+    fn is_line_start(&self, offset: usize) -> bool {
+        self.line_index
+            .line_range(self.line(offset) + 1)
+            .is_some_and(|range| range.start == offset)
+    }
+}
+
+/// A run of whole lines that differ between the original and rewritten texts
+///
+/// Indices are 0-based, and ranges are half-open.
+#[derive(Debug)]
+struct Block {
+    original: (usize, usize),
+    rewritten: (usize, usize),
+}
+
+fn blocks(original: &Lines, rewritten: &Lines, edits: &[Edit]) -> Vec<Block> {
+    let mut blocks = Vec::<Block>::new();
+
+    for edit in edits {
+        let start = (
+            original.line(edit.original.start),
+            rewritten.line(edit.rewritten.start),
+        );
+
+        // smoelius: The text following an edit is unchanged. So if both sides of the edit end at
+        // the start of a line, that line is unchanged. Otherwise, the line containing the end of
+        // the edit is changed.
+        let end = if original.is_line_start(edit.original.end)
+            && rewritten.is_line_start(edit.rewritten.end)
+        {
+            (
+                original.line(edit.original.end),
+                rewritten.line(edit.rewritten.end),
+            )
+        } else {
+            (
+                original.line(edit.original.end) + 1,
+                rewritten.line(edit.rewritten.end) + 1,
+            )
+        };
+
+        let end = (
+            end.0.min(original.lines.len()),
+            end.1.min(rewritten.lines.len()),
+        );
+
+        // smoelius: Merge adjacent blocks so that their removed lines precede their added lines.
+        if let Some(block) = blocks.last_mut()
+            && start.0 <= block.original.1
+        {
+            block.original.1 = block.original.1.max(end.0);
+            block.rewritten.1 = block.rewritten.1.max(end.1);
+            continue;
+        }
+
+        blocks.push(Block {
+            original: (start.0, end.0),
+            rewritten: (start.1, end.1),
+        });
+    }
+
+    blocks.retain(|block| {
+        original.lines[block.original.0..block.original.1]
+            != rewritten.lines[block.rewritten.0..block.rewritten.1]
+    });
+
+    blocks
+}
+
+fn hunks(original: &Lines, rewritten: &Lines, blocks: &[Block], context: usize) -> Vec<Hunk> {
+    let mut hunks = Vec::new();
+
+    let mut iter = blocks.iter().peekable();
+    while let Some(first) = iter.next() {
+        let mut group = vec![first];
+        while let Some(next) = iter.peek() {
+            let last = group.last().unwrap();
+            if next.original.0 - last.original.1 > 2 * context {
+                break;
+            }
+            group.push(next);
+            iter.next();
+        }
+
+        let first = group.first().unwrap();
+        let last = group.last().unwrap();
+
+        let leading = first.original.0.min(context);
+        let trailing = (original.lines.len() - last.original.1).min(context);
+
+        let mut lines = Vec::new();
+        let mut original_index = first.original.0 - leading;
+        for block in &group {
+            for &line in &original.lines[original_index..block.original.0] {
+                lines.push(Line::Context(line.to_owned()));
+            }
+            for &line in &original.lines[block.original.0..block.original.1] {
+                lines.push(Line::Removed(line.to_owned()));
+            }
+            for &line in &rewritten.lines[block.rewritten.0..block.rewritten.1] {
+                lines.push(Line::Added(line.to_owned()));
+            }
+            original_index = block.original.1;
+        }
+        for &line in &original.lines[original_index..last.original.1 + trailing] {
+            lines.push(Line::Context(line.to_owned()));
+        }
+
+        let original_start = first.original.0 - leading;
+        let original_len = last.original.1 + trailing - original_start;
+        let rewritten_start = first.rewritten.0 - leading;
+        let rewritten_len = last.rewritten.1 + trailing - rewritten_start;
+
+        hunks.push(Hunk {
+            original_start: header_start(original_start, original_len),
+            original_len,
+            rewritten_start: header_start(rewritten_start, rewritten_len),
+            rewritten_len,
+            lines,
+        });
+    }
+
+    hunks
+}
+
+/// Converts a 0-based line index to a hunk header's start
+fn header_start(index: usize, len: usize) -> usize {
+    if len == 0 { index } else { index + 1 }
+}
@@ -0,0 +1,133 @@
+use crate::SpanDefault;
+use crate::interface::Span;
+use crate::rewriter::Rewriter;
+use std::fmt::{Display, Formatter};
+
+/// A collection of rewrites that may be added in any order
+///
+/// [`Rewriter::rewrite`] requires that each span start at or after the end of the previous one. An
+/// `EditSet` lifts that requirement: edits are sorted by their start and end [`LineColumn`]s before
+/// they are applied. Edits with identical spans are applied in the order they were added.
+///
+/// [`LineColumn`]: crate::interface::LineColumn
+#[derive(Debug)]
+pub struct EditSet<S: Span = SpanDefault> {
+    edits: Vec<(S, String)>,
+}
+
+/// Two edits in an [`EditSet`] whose spans overlap
+#[derive(Clone, Debug)]
+pub struct Overlap<S: Span = SpanDefault> {
+    pub first: S,
+    pub second: S,
+}
+
+impl<S: Span> Default for EditSet<S> {
+    fn default() -> Self {
+        Self { edits: Vec::new() }
+    }
+}
+
+impl<S: Span> EditSet<S> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, span: S, replacement: impl Into<String>) {
+        self.edits.push((span, replacement.into()));
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Returns each pair of edits whose spans overlap
+    ///
+    /// An empty span (i.e., an insertion) at the start or end of another span is not considered to
+    /// overlap it.
+    #[must_use]
+    pub fn overlaps(&self) -> Vec<Overlap<S>> {
+        let mut edits = self.edits.iter().map(|(span, _)| span).collect::<Vec<_>>();
+        edits.sort_by_key(|span| (span.start(), span.end()));
+
+        // smoelius: `active` holds the earlier spans that end after the current span starts. Since
+        // the spans are sorted by start, each of them overlaps the current span.
+        let mut overlaps = Vec::new();
+        let mut active: Vec<&S> = Vec::new();
+        for span in edits {
+            active.retain(|first| span.start() < first.end());
+            overlaps.extend(active.iter().map(|&first| Overlap {
+                first: first.clone(),
+                second: span.clone(),
+            }));
+            active.push(span);
+        }
+        overlaps
+    }
+
+    /// Applies the edits to `original` and returns the resulting text
+    ///
+    /// Returns the first [`Overlap`] found, if any, in which case no edits are applied.
+    pub fn apply(self, original: &str) -> Result<String, Overlap<S>> {
+        let mut rewriter = Rewriter::new(original);
+        self.apply_to(&mut rewriter)?;
+        Ok(rewriter.contents())
+    }
+
+    /// Applies the edits using `rewriter`
+    ///
+    /// Returns the first [`Overlap`] found, if any, in which case no edits are applied. Like
+    /// [`Rewriter::rewrite`], panics if an edit starts before the end of a span already passed to
+    /// `rewriter`.
+    pub fn apply_to(mut self, rewriter: &mut Rewriter<'_, S>) -> Result<(), Overlap<S>> {
+        if let Some(overlap) = self.overlaps().into_iter().next() {
+            return Err(overlap);
+        }
+
+        // smoelius: `sort_by_key` is stable, so edits with identical spans keep their order.
+        self.edits
+            .sort_by_key(|(span, _)| (span.start(), span.end()));
+
+        for (span, replacement) in &self.edits {
+            let _: String = rewriter.rewrite(span, replacement);
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: Span> Extend<(S, String)> for EditSet<S> {
+    fn extend<T: IntoIterator<Item = (S, String)>>(&mut self, iter: T) {
+        self.edits.extend(iter);
+    }
+}
+
+impl<S: Span> FromIterator<(S, String)> for EditSet<S> {
+    fn from_iter<T: IntoIterator<Item = (S, String)>>(iter: T) -> Self {
+        Self {
+            edits: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<S: Span> Display for Overlap<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "span {:?}..{:?} overlaps span {:?}..{:?}",
+            self.first.start(),
+            self.first.end(),
+            self.second.start(),
+            self.second.end(),
+        )
+    }
+}
+
+impl<S: Span> std::error::Error for Overlap<S> {}
@@ -0,0 +1,65 @@
+use crate::{SpanDefault, interface};
+use std::fmt::{Debug, Display, Formatter};
+
+/// An error returned by [`Rewriter::try_rewrite`] or [`OffsetCalculator::try_offsets_from_span`]
+///
+/// [`OffsetCalculator::try_offsets_from_span`]: crate::OffsetCalculator::try_offsets_from_span
+/// [`Rewriter::try_rewrite`]: crate::Rewriter::try_rewrite
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RewriteError<L = <SpanDefault as interface::Span>::LineColumn> {
+    /// The span starts before the end of the previously rewritten span
+    OutOfOrder { previous_end: L, start: L },
+    /// The span ends before it starts
+    ReversedSpan { start: L, end: L },
+    /// The line is 0 or greater than the number of lines in the file
+    LineOutOfBounds { line_column: L },
+    /// The column is past the end of a line other than the file's last
+    ColumnPastEndOfLine { line_column: L, end_of_line: L },
+    /// The column is past the end of the file's last line
+    PastEndOfFile { line_column: L, end_of_file: L },
+    /// The span does not belong to the original text, e.g., it is from a different file
+    ForeignSpan { start: L, end: L },
+    /// The span does not refer to a location in any text, e.g., it is `Span::call_site()`
+    NoLocation,
+}
+
+impl<L: Debug> Display for RewriteError<L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfOrder {
+                previous_end,
+                start,
+            } => write!(
+                f,
+                "span starts at {start:?}, before the end of the previous span at {previous_end:?}"
+            ),
+            Self::ReversedSpan { start, end } => {
+                write!(f, "span ends at {end:?}, before its start at {start:?}")
+            }
+            Self::LineOutOfBounds { line_column } => {
+                write!(f, "line of {line_column:?} is out of bounds")
+            }
+            Self::ColumnPastEndOfLine {
+                line_column,
+                end_of_line,
+            } => write!(
+                f,
+                "column of {line_column:?} is past the end of the line at {end_of_line:?}"
+            ),
+            Self::PastEndOfFile {
+                line_column,
+                end_of_file,
+            } => write!(
+                f,
+                "{line_column:?} is past the end of the file at {end_of_file:?}"
+            ),
+            Self::ForeignSpan { start, end } => write!(
+                f,
+                "span from {start:?} to {end:?} does not belong to the original text"
+            ),
+            Self::NoLocation => write!(f, "span has no location"),
+        }
+    }
+}
+
+impl<L: Debug> std::error::Error for RewriteError<L> {}
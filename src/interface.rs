@@ -1,10 +1,157 @@
-use std::fmt::Debug;
+use crate::ColumnEncoding;
+use std::{fmt::Debug, ops::Range};
 
 pub trait Span: Clone + Debug {
     type LineColumn: self::LineColumn;
+
+    /// The unit in which the span type's columns are counted
+    ///
+    /// Constructors such as [`Rewriter::new`] interpret columns in this encoding. Constructors such
+    /// as [`Rewriter::with_encoding`] override it.
+    ///
+    /// [`Rewriter::new`]: crate::Rewriter::new
+    /// [`Rewriter::with_encoding`]: crate::Rewriter::with_encoding
+    const COLUMN_ENCODING: ColumnEncoding = ColumnEncoding::Chars;
+
     fn line_column(line: usize, column: usize) -> Self::LineColumn;
     fn start(&self) -> Self::LineColumn;
     fn end(&self) -> Self::LineColumn;
+
+    /// Returns the span's byte offsets into the text it was parsed from, if they are known without
+    /// resolving its lines and columns
+    ///
+    /// The byte offsets are used only when lines and columns are counted as the span type counts
+    /// them, i.e., in [`Span::COLUMN_ENCODING`] with the default [`LineTerminators`].
+    ///
+    /// [`LineTerminators`]: crate::LineTerminators
+    fn byte_range(&self) -> Option<Range<usize>> {
+        None
+    }
+
+    /// Returns whether the span refers to a location in some text
+    ///
+    /// A span with no location (e.g., `proc_macro2::Span::call_site()`) is rejected rather than
+    /// rewritten. The default implementation returns `true`.
+    fn has_location(&self) -> bool {
+        true
+    }
+
+    /// Returns whether the span and `other` come from the same text
+    ///
+    /// A [`Rewriter`] rejects spans that do not come from the same text as its source span (see
+    /// [`Rewriter::set_source_span`]). The default implementation returns `true`.
+    ///
+    /// [`Rewriter`]: crate::Rewriter
+    /// [`Rewriter::set_source_span`]: crate::Rewriter::set_source_span
+    fn same_source(&self, other: &Self) -> bool {
+        let _ = other;
+        true
+    }
+
+    /// Returns whether the span could have come from `original`, given that it was resolved to
+    /// `original[range]`
+    ///
+    /// This method is used only to check the span that becomes a [`Rewriter`]'s source span when
+    /// none was set. `range` may not be within `original`. The default implementation returns
+    /// `true`.
+    ///
+    /// An implementation may be unable to give a definitive answer. For example, the
+    /// implementation for `proc_macro2::Span` compares the text the span covers to
+    /// `original[range]`, and so accepts a span from a different text that shares that text at
+    /// that position. Use [`Rewriter::set_source_span`] when such spans must be rejected.
+    ///
+    /// [`Rewriter`]: crate::Rewriter
+    /// [`Rewriter::set_source_span`]: crate::Rewriter::set_source_span
+    fn belongs_to(&self, original: &str, range: Range<usize>) -> bool {
+        let _ = (original, range);
+        true
+    }
+
+    /// Returns the span's start and end as a `Range`
+    fn to_range(&self) -> Range<Self::LineColumn> {
+        self.start()..self.end()
+    }
+
+    /// Returns whether the span starts where it ends
+    fn is_empty(&self) -> bool {
+        self.start() == self.end()
+    }
+
+    /// Returns whether `line_column` is at or after the span's start and before its end
+    ///
+    /// So an empty span contains nothing.
+    fn contains(&self, line_column: Self::LineColumn) -> bool {
+        self.start() <= line_column && line_column < self.end()
+    }
+
+    /// Returns whether `other` starts at or after the span's start and ends at or before its end
+    fn contains_span(&self, other: &Self) -> bool {
+        self.start() <= other.start() && other.end() <= self.end()
+    }
+
+    /// Returns whether the span and `other` share a position, as in [`EditSet::overlaps`]
+    ///
+    /// An empty span at the start or end of another span does not overlap it.
+    ///
+    /// [`EditSet::overlaps`]: crate::EditSet::overlaps
+    fn overlaps(&self, other: &Self) -> bool {
+        self.start() < other.end() && other.start() < self.end()
+    }
+}
+
+/// A [`Span`] that can be constructed from its start and end
+///
+/// This trait is not implemented for `proc_macro2::Span`, which cannot be constructed this way.
+pub trait FromLineColumns: Span {
+    fn from_line_columns(start: Self::LineColumn, end: Self::LineColumn) -> Self;
+
+    fn from_range(range: Range<Self::LineColumn>) -> Self {
+        Self::from_line_columns(range.start, range.end)
+    }
+
+    /// Returns the span of positions in both the span and `other`, or `None` if there are none
+    ///
+    /// Spans that touch (i.e., one ends where the other starts) intersect in an empty span.
+    #[must_use]
+    fn intersect(&self, other: &Self) -> Option<Self> {
+        let start = self.start().max(other.start());
+        let end = self.end().min(other.end());
+        (start <= end).then(|| Self::from_line_columns(start, end))
+    }
+
+    /// Returns the smallest span containing both the span and `other`
+    #[must_use]
+    fn cover(&self, other: &Self) -> Self {
+        Self::from_line_columns(self.start().min(other.start()), self.end().max(other.end()))
+    }
+
+    /// Returns an empty span at the span's start
+    #[must_use]
+    fn shrink_to_start(&self) -> Self {
+        Self::from_line_columns(self.start(), self.start())
+    }
+
+    /// Returns an empty span at the span's end
+    #[must_use]
+    fn shrink_to_end(&self) -> Self {
+        Self::from_line_columns(self.end(), self.end())
+    }
+
+    /// Returns the span from the end of the span to the start of `other`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `other` starts before the span ends.
+    #[must_use]
+    fn between(&self, other: &Self) -> Self {
+        assert!(
+            self.end() <= other.start(),
+            "{:?} starts before {:?}",
+            other.start(),
+            self.end()
+        );
+        Self::from_line_columns(self.end(), other.start())
+    }
 }
 
 pub trait LineColumn: Copy + Debug + Ord {
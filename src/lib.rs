@@ -1,22 +1,67 @@
 mod backup;
 pub use backup::Backup;
 
+mod byte_rewriter;
+pub use byte_rewriter::ByteRewriter;
+
+mod column_encoding;
+pub use column_encoding::ColumnEncoding;
+
+pub mod diff;
+
+mod edit_set;
+pub use edit_set::{EditSet, Overlap};
+
+mod error;
+pub use error::RewriteError;
+
 pub mod interface;
 
+mod line_ending;
+pub use line_ending::{LineEnding, LineEndingPolicy, TrailingNewline};
+
+mod line_index;
+pub use line_index::LineIndex;
+
 mod offset_based_rewriter;
 
 mod offset_calculator;
-pub use offset_calculator::OffsetCalculator;
+pub use offset_calculator::{OffsetCalculator, OwnedOffsetCalculator, SharedOffsetCalculator};
+
+mod owned_rewriter;
+pub use owned_rewriter::OwnedRewriter;
+
+pub mod patch;
 
 mod rewriter;
-pub use rewriter::Rewriter;
+pub use rewriter::{Checkpoint, Rewriter};
+
+mod source_map;
+pub use source_map::{Edit, SourceMap};
 
 mod span;
-pub use span::{LineColumn, Span};
+pub use span::{ByteSpan, LineColumn, Span};
+
+mod text_file;
+pub use text_file::{FileEncoding, TextFile};
+
+mod text_format;
+pub use text_format::{LineTerminators, TextFormat};
+
+#[cfg(feature = "proc-macro2-impl")]
+mod multi_file_rewriter;
+#[cfg(feature = "proc-macro2-impl")]
+pub use multi_file_rewriter::MultiFileRewriter;
 
 #[cfg(feature = "proc-macro2-impl")]
 mod proc_macro2_impl;
 
+#[cfg(feature = "syn")]
+mod syn_impl;
+
+#[cfg(feature = "tree-sitter")]
+mod tree_sitter_impl;
+
 #[cfg(not(feature = "proc-macro2-span"))]
 type SpanDefault = crate::Span;
 
@@ -0,0 +1,169 @@
+use crate::{Edit, LineTerminators};
+use std::borrow::Cow;
+
+/// A line break sequence
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+/// How a [`Rewriter`] treats line breaks in replacements and at the end of its contents
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct LineEndingPolicy {
+    /// Whether to convert the line breaks (`\n` and `\r\n`) in each replacement to the original
+    /// text's dominant [`LineEnding`]
+    pub normalize_replacements: bool,
+    pub trailing_newline: TrailingNewline,
+}
+
+/// Whether the rewritten contents end with a line break
+///
+/// When a line break is added, it is the original text's dominant [`LineEnding`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TrailingNewline {
+    /// Leave the end of the contents as the rewrites left it
+    #[default]
+    Unchanged,
+    /// End the contents with a line break if and only if the original text ends with one
+    Keep,
+    /// Always end the contents with a line break
+    Ensure,
+}
+
+/// A change to the end of the rewritten contents required by a [`TrailingNewline`] policy
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum TrailingNewlineFix {
+    Append(LineEnding),
+    /// Remove the last `n` bytes
+    Truncate(usize),
+}
+
+impl LineEnding {
+    /// Returns the line ending used most often in `text`, or `None` if `text` contains no line
+    /// breaks
+    ///
+    /// Ties are broken in favor of [`LineEnding::Lf`].
+    #[must_use]
+    pub fn detect(text: &str) -> Option<Self> {
+        let n_crlf = text.matches("\r\n").count();
+        let n_lf = text.matches('\n').count() - n_crlf;
+        if n_crlf == 0 && n_lf == 0 {
+            None
+        } else if n_lf < n_crlf {
+            Some(Self::Crlf)
+        } else {
+            Some(Self::Lf)
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+        }
+    }
+
+    /// Converts the line breaks in `text` to `self`
+    pub(crate) fn normalize(self, text: &str) -> Cow<'_, str> {
+        match self {
+            Self::Lf if text.contains("\r\n") => Cow::Owned(text.replace("\r\n", "\n")),
+            Self::Crlf if text.contains('\n') => {
+                Cow::Owned(text.replace("\r\n", "\n").replace('\n', "\r\n"))
+            }
+            _ => Cow::Borrowed(text),
+        }
+    }
+}
+
+impl TrailingNewline {
+    /// Makes `contents` satisfy `self`, and updates `edits`, which relate `original` to `contents`,
+    /// to match
+    ///
+    /// Line breaks are recognized according to `line_terminators`. Only the last two of `edits` are
+    /// read. Earlier edits are left unchanged.
+    pub(crate) fn apply(
+        self,
+        original: &str,
+        line_ending: LineEnding,
+        line_terminators: LineTerminators,
+        contents: &mut String,
+        edits: &mut Vec<Edit>,
+    ) {
+        if let Some(fix) = self.fix(original, contents, line_ending, line_terminators) {
+            fix.apply(original, contents, edits);
+        }
+    }
+
+    /// Returns the change, if any, needed for `contents` to satisfy `self`
+    fn fix(
+        self,
+        original: &str,
+        contents: &str,
+        line_ending: LineEnding,
+        line_terminators: LineTerminators,
+    ) -> Option<TrailingNewlineFix> {
+        let wanted = match self {
+            Self::Unchanged => return None,
+            Self::Keep => line_terminators.trailing_len(original) != 0,
+            Self::Ensure => true,
+        };
+        let n = line_terminators.trailing_len(contents);
+        match (wanted, n) {
+            (true, 0) => Some(TrailingNewlineFix::Append(line_ending)),
+            (false, 1..) => Some(TrailingNewlineFix::Truncate(n)),
+            _ => None,
+        }
+    }
+}
+
+impl TrailingNewlineFix {
+    /// Applies the fix to `contents`, and updates `edits`, which relate `original` to `contents`,
+    /// to match
+    fn apply(self, original: &str, contents: &mut String, edits: &mut Vec<Edit>) {
+        match self {
+            Self::Append(line_ending) => {
+                let start = contents.len();
+                contents.push_str(line_ending.as_str());
+                edits.push(Edit {
+                    original: original.len()..original.len(),
+                    rewritten: start..contents.len(),
+                });
+            }
+            Self::Truncate(n) => {
+                // smoelius: The removed text is recorded as part of the last edit if it overlaps
+                // or follows that edit's replacement directly. Otherwise, it is recorded as a new
+                // edit. Text within an earlier edit's replacement is never removed. A line
+                // terminator is removed whole or not at all, so if it overlaps such a replacement
+                // (e.g., the replacement ends with the `\r` of a `\r\n`), nothing is removed.
+                let floor = edits
+                    .len()
+                    .checked_sub(2)
+                    .map_or(0, |index| edits[index].rewritten.end);
+                let len = contents.len() - n;
+                if len < floor {
+                    return;
+                }
+                match edits.last_mut() {
+                    Some(edit) if len <= edit.rewritten.end => {
+                        if len < edit.rewritten.start {
+                            edit.original.start -= edit.rewritten.start - len;
+                            edit.rewritten.start = len;
+                        }
+                        edit.original.end = original.len();
+                        edit.rewritten.end = len;
+                    }
+                    _ => edits.push(Edit {
+                        original: original.len() - (contents.len() - len)..original.len(),
+                        rewritten: len..len,
+                    }),
+                }
+                contents.truncate(len);
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+use crate::interface::LineColumn;
+use crate::{ColumnEncoding, RewriteError, TextFormat};
+use std::ops::Range;
+
+/// The start of each line of a text, computed once
+///
+/// A `LineIndex` converts line-column pairs to byte offsets without rescanning the text. Finding
+/// a line's start takes O(1) time, and finding the line containing an offset takes O(log n) time.
+/// Lines that are pure ASCII (and, for [`ColumnEncoding::Visual`], contain no tabs) are flagged, so
+/// columns within them are converted in O(1) time.
+/// Columns within other lines require scanning the line up to the column.
+///
+/// Lines and columns are counted according to the index's [`TextFormat`]. Methods that take a
+/// `text` argument must be passed the text from which the `LineIndex` was built.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LineIndex {
+    lines: Vec<Line>,
+    len: usize,
+    format: TextFormat,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Line {
+    start: usize,
+    end: usize,
+    /// Whether the line is pure ASCII
+    ascii: bool,
+    /// Whether each byte of the line is one column
+    uniform: bool,
+    /// Whether all characters before the line are ASCII
+    ascii_before: bool,
+}
+
+impl LineIndex {
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self::with_encoding(text, ColumnEncoding::default())
+    }
+
+    #[must_use]
+    pub fn with_encoding(text: &str, encoding: ColumnEncoding) -> Self {
+        Self::with_format(text, encoding.into())
+    }
+
+    #[must_use]
+    pub fn with_format(text: &str, format: TextFormat) -> Self {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        let mut ascii_before = true;
+
+        loop {
+            let terminator = format.line_terminators.find(&text[start..]);
+            let end = terminator.map_or(text.len(), |(index, _)| start + index);
+            let bytes = &text.as_bytes()[start..end];
+            let ascii = bytes.is_ascii();
+            let uniform =
+                ascii && !(format.column_encoding.expands_tabs() && bytes.contains(&b'\t'));
+            lines.push(Line {
+                start,
+                end,
+                ascii,
+                uniform,
+                ascii_before,
+            });
+            let Some((_, len)) = terminator else {
+                break;
+            };
+            // smoelius: The terminator need not be ASCII (e.g., U+2028).
+            ascii_before &= text.as_bytes()[start..end + len].is_ascii();
+            start = end + len;
+        }
+
+        Self {
+            lines,
+            len: text.len(),
+            format,
+        }
+    }
+
+    #[must_use]
+    pub fn encoding(&self) -> ColumnEncoding {
+        self.format.column_encoding
+    }
+
+    #[must_use]
+    pub fn format(&self) -> TextFormat {
+        self.format
+    }
+
+    /// Returns the number of lines, which is always at least 1
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Returns the byte range of `line`, not including its terminator
+    ///
+    /// Lines are 1-based. Returns `None` if `line` is 0 or greater than the number of lines.
+    #[must_use]
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        self.line(line).map(|line| line.start..line.end)
+    }
+
+    /// Returns whether `line` is pure ASCII, or `None` if there is no such line
+    #[must_use]
+    pub fn is_ascii_line(&self, line: usize) -> Option<bool> {
+        self.line(line).map(|line| line.ascii)
+    }
+
+    /// Returns the 1-based line containing `offset`, or `None` if `offset` is past the end of the
+    /// text
+    ///
+    /// An offset within a line's terminator is considered part of that line.
+    #[must_use]
+    pub fn line_from_offset(&self, offset: usize) -> Option<usize> {
+        if self.len < offset {
+            return None;
+        }
+        Some(self.lines.partition_point(|line| line.start <= offset))
+    }
+
+    /// Returns the 1-based line and 0-based column of `offset`, or `None` if `offset` is past the
+    /// end of the text
+    ///
+    /// An offset inside a character is rounded down to the start of the character, and an offset
+    /// within a line's terminator is rounded down to the end of the line.
+    pub(crate) fn line_and_column(&self, text: &str, offset: usize) -> Option<(usize, usize)> {
+        debug_assert_eq!(self.len, text.len());
+
+        let line = self.line_from_offset(offset)?;
+        let &Line {
+            start,
+            end,
+            uniform,
+            ..
+        } = self.line(line)?;
+        let offset = offset.min(end);
+
+        if uniform {
+            return Some((line, offset - start));
+        }
+
+        let column = self
+            .format
+            .column_encoding
+            .columns(&text[start..end], offset - start);
+
+        Some((line, column))
+    }
+
+    /// Returns the byte offset in `text` for `line_column`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `line_column` is not within `text`.
+    pub fn offset_from_line_column<L: LineColumn>(
+        &self,
+        text: &str,
+        line_column: L,
+    ) -> Result<usize, RewriteError<L>> {
+        self.try_offset_and_ascii(text, line_column)
+            .map(|(offset, _)| offset)
+    }
+
+    /// Like [`LineIndex::offset_from_line_column`], but also returns whether all characters up to
+    /// the offset are ASCII
+    pub(crate) fn try_offset_and_ascii<L: LineColumn>(
+        &self,
+        text: &str,
+        line_column: L,
+    ) -> Result<(usize, bool), RewriteError<L>> {
+        debug_assert_eq!(self.len, text.len());
+
+        let Some(line) = self.line(line_column.line()) else {
+            return Err(RewriteError::LineOutOfBounds { line_column });
+        };
+
+        let (offset, ascii, n) =
+            line.advance(text, self.format.column_encoding, line_column.column());
+
+        if n < line_column.column() && line.start + offset == line.end {
+            let last = line_column.line() == self.lines.len();
+            return Err(past_end(line_column, n, last));
+        }
+
+        Ok((line.start + offset, line.ascii_before && ascii))
+    }
+
+    /// Like [`LineIndex::try_offset_and_ascii`], but clamps rather than returning an error
+    ///
+    /// A column past the end of its line is clamped to the end of the line. A line that is not
+    /// within `text` is treated as starting just past the end of `text`, as
+    /// `CachingOffsetCalculator` does when scanning forward.
+    pub(crate) fn offset_and_ascii<L: LineColumn>(
+        &self,
+        text: &str,
+        line_column: L,
+    ) -> (usize, bool) {
+        debug_assert_eq!(self.len, text.len());
+
+        let Some(line) = self.line(line_column.line()) else {
+            let last = self.lines.last().unwrap();
+            return (self.len + 1, last.ascii_before && last.ascii);
+        };
+
+        let (offset, ascii, _) =
+            line.advance(text, self.format.column_encoding, line_column.column());
+
+        (line.start + offset, line.ascii_before && ascii)
+    }
+
+    fn line(&self, line: usize) -> Option<&Line> {
+        self.lines.get(line.checked_sub(1)?)
+    }
+}
+
+impl Line {
+    /// Advances through the line by up to `n` columns
+    ///
+    /// See [`ColumnEncoding::advance`].
+    fn advance(&self, text: &str, encoding: ColumnEncoding, n: usize) -> (usize, bool, usize) {
+        if self.uniform {
+            let count = n.min(self.end - self.start);
+            (count, true, count)
+        } else {
+            encoding.advance(&text[self.start..self.end], 0, n)
+        }
+    }
+}
+
+/// Returns the error for `line_column`, whose column is past the end of its line
+///
+/// `end_column` is the line's length in columns, and `last` indicates whether the line is the
+/// file's last.
+pub(crate) fn past_end<L: LineColumn>(
+    mut line_column: L,
+    end_column: usize,
+    last: bool,
+) -> RewriteError<L> {
+    let offending = line_column;
+    *line_column.column_mut() = end_column;
+    if last {
+        RewriteError::PastEndOfFile {
+            line_column: offending,
+            end_of_file: line_column,
+        }
+    } else {
+        RewriteError::ColumnPastEndOfLine {
+            line_column: offending,
+            end_of_line: line_column,
+        }
+    }
+}
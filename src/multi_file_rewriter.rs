@@ -0,0 +1,146 @@
+use crate::{OwnedRewriter, RewriteError, interface::Span as _};
+use proc_macro2::{LexError, LineColumn, Span, TokenStream};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+/// Rewrites several files using `proc_macro2::Span`s, routing each span to the file it came from
+///
+/// Each file is parsed with [`MultiFileRewriter::parse`], which records where proc-macro2 placed
+/// the file's tokens. A span passed to [`MultiFileRewriter::rewrite`] is then rewritten in the file
+/// whose tokens it can be joined with (see [`proc_macro2::Span::join`]). Within each file, spans
+/// must be in order, as with a [`Rewriter`]. Spans of different files may be interleaved.
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Debug, Default)]
+pub struct MultiFileRewriter {
+    files: Vec<File>,
+    /// The index of the file to which a span was most recently routed
+    last: usize,
+}
+
+#[derive(Debug)]
+struct File {
+    path: PathBuf,
+    /// The span of the file's first token, or `None` if the file has no tokens
+    span: Option<Span>,
+    rewriter: OwnedRewriter<Span>,
+}
+
+impl MultiFileRewriter {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses `original` and adds it to the `MultiFileRewriter` as the contents of `path`
+    ///
+    /// The returned tokens' spans can be passed to [`MultiFileRewriter::rewrite`], e.g., after the
+    /// tokens are parsed with `syn::parse2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` was already added.
+    pub fn parse(
+        &mut self,
+        path: impl Into<PathBuf>,
+        original: impl Into<Arc<str>>,
+    ) -> Result<TokenStream, LexError> {
+        let path = path.into();
+        let original = original.into();
+
+        assert!(
+            self.files.iter().all(|file| file.path != path),
+            "{} was already added",
+            path.display()
+        );
+
+        let tokens = TokenStream::from_str(&original)?;
+
+        let span = tokens.clone().into_iter().next().map(|tree| tree.span());
+        let mut rewriter = OwnedRewriter::new(original);
+        if let Some(span) = span {
+            rewriter.set_source_span(span);
+        }
+
+        self.files.push(File {
+            path,
+            span,
+            rewriter,
+        });
+
+        Ok(tokens)
+    }
+
+    /// Returns the path of the file `span` came from, if that file was added
+    #[must_use]
+    pub fn path(&self, span: Span) -> Option<&Path> {
+        self.position(span)
+            .map(|index| self.files[index].path.as_path())
+    }
+
+    /// Rewrites `span` in the file it came from, and returns the replaced text
+    ///
+    /// # Panics
+    ///
+    /// Panics if `span` has no location, if `span` did not come from an added file, or if
+    /// [`OwnedRewriter::rewrite`] would panic.
+    pub fn rewrite(&mut self, span: Span, replacement: &str) -> String {
+        assert!(span.has_location(), "span has no location: {span:?}");
+        let Some(index) = self.route(span) else {
+            panic!("span did not come from an added file: {span:?}");
+        };
+        self.files[index].rewriter.rewrite(&span, replacement)
+    }
+
+    /// Like [`MultiFileRewriter::rewrite`], but returns an error rather than panicking
+    ///
+    /// A span with no location results in [`RewriteError::NoLocation`], and one that did not come
+    /// from an added file results in [`RewriteError::ForeignSpan`].
+    pub fn try_rewrite(
+        &mut self,
+        span: Span,
+        replacement: &str,
+    ) -> Result<String, RewriteError<LineColumn>> {
+        if !span.has_location() {
+            return Err(RewriteError::NoLocation);
+        }
+        let Some(index) = self.route(span) else {
+            return Err(RewriteError::ForeignSpan {
+                start: span.start(),
+                end: span.end(),
+            });
+        };
+        self.files[index].rewriter.try_rewrite(&span, replacement)
+    }
+
+    /// Returns each added file's path and rewritten contents
+    ///
+    /// Files that were not rewritten are included, with their original contents.
+    #[must_use]
+    pub fn contents(self) -> BTreeMap<PathBuf, String> {
+        self.files
+            .into_iter()
+            .map(|file| (file.path, file.rewriter.contents()))
+            .collect()
+    }
+
+    fn route(&mut self, span: Span) -> Option<usize> {
+        let index = self.position(span)?;
+        self.last = index;
+        Some(index)
+    }
+
+    // smoelius: Consecutive spans usually come from the same file, so the most recently used file
+    // is tried first.
+    fn position(&self, span: Span) -> Option<usize> {
+        let belongs = |file: &File| file.span.is_some_and(|first| first.join(span).is_some());
+        if self.files.get(self.last).is_some_and(belongs) {
+            return Some(self.last);
+        }
+        self.files.iter().position(belongs)
+    }
+}
@@ -1,85 +1,134 @@
-use super::Interface;
+use super::{Interface, Text};
 
 #[derive(Debug)]
-pub struct LazyRewriter<'original> {
-    original: &'original str,
-    rewritten: String,
+pub struct LazyRewriter<T: Text + ?Sized> {
+    rewritten: T::Buf,
     offset: usize,
 }
 
 #[derive(Debug)]
-pub struct EagerRewriter {
-    rewritten: String,
+pub struct EagerRewriter<T: Text + ?Sized> {
+    rewritten: T::Buf,
     delta: isize,
 }
 
-impl<'original> LazyRewriter<'original> {
-    pub fn new(original: &'original str) -> Self {
+// smoelius: `Clone` is implemented manually because `derive` would require `T: Clone`.
+impl<T: Text + ?Sized> Clone for LazyRewriter<T> {
+    fn clone(&self) -> Self {
         Self {
-            original,
-            rewritten: String::new(),
+            rewritten: self.rewritten.clone(),
+            offset: self.offset,
+        }
+    }
+}
+
+impl<T: Text + ?Sized> Clone for EagerRewriter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            rewritten: self.rewritten.clone(),
+            delta: self.delta,
+        }
+    }
+}
+
+impl<T: Text + ?Sized> LazyRewriter<T> {
+    pub fn new() -> Self {
+        Self {
+            rewritten: T::Buf::default(),
             offset: 0,
         }
     }
 }
 
-impl EagerRewriter {
+impl<T: Text + ?Sized> EagerRewriter<T> {
     #[allow(dead_code)]
-    pub fn new(original: &str) -> Self {
+    pub fn new(original: &T) -> Self {
         Self {
-            rewritten: original.to_owned(),
+            rewritten: original.to_buf(),
             delta: 0,
         }
     }
 }
 
-impl Interface for LazyRewriter<'_> {
-    fn contents(mut self) -> String {
-        self.rewritten += &self.original[self.offset..];
+impl<T: Text + ?Sized> Interface<T> for LazyRewriter<T> {
+    /// The length of `rewritten` and the value of `offset`
+    type Checkpoint = (usize, usize);
+
+    fn contents(mut self, original: &T) -> T::Buf {
+        T::push(
+            &mut self.rewritten,
+            original.slice(self.offset..original.len()),
+        );
 
         self.rewritten
     }
 
-    fn rewrite(&mut self, start: usize, end: usize, replacement: &str) -> String {
+    fn preview(&self, original: &T) -> T::Buf {
+        let mut preview = self.rewritten.clone();
+        T::push(&mut preview, original.slice(self.offset..original.len()));
+        preview
+    }
+
+    fn rewrite(&mut self, original: &T, start: usize, end: usize, replacement: &T) -> T::Buf {
         assert!(self.offset <= start);
 
-        if self.original.len() <= start {
-            return String::new();
+        // smoelius: An empty span at the end of the original text is an insertion, e.g., of a
+        // trailing newline, and is applied like any other.
+        if original.len() < start {
+            return T::Buf::default();
         }
 
-        let end = std::cmp::min(end, self.original.len());
+        let end = std::cmp::min(end, original.len());
 
-        self.rewritten += &self.original[self.offset..start];
-        self.rewritten += replacement;
+        T::push(&mut self.rewritten, original.slice(self.offset..start));
+        T::push(&mut self.rewritten, replacement);
 
         self.offset = end;
 
-        String::from(&self.original[self.offset - (end - start)..self.offset])
+        original.slice(start..end).to_buf()
+    }
+
+    fn checkpoint(&self) -> Self::Checkpoint {
+        (T::as_text(&self.rewritten).len(), self.offset)
+    }
+
+    fn rollback_to(&mut self, (len, offset): Self::Checkpoint) {
+        assert!(len <= T::as_text(&self.rewritten).len() && offset <= self.offset);
+        T::truncate(&mut self.rewritten, len);
+        self.offset = offset;
     }
 }
 
-impl Interface for EagerRewriter {
-    fn contents(self) -> String {
+impl<T: Text + ?Sized> Interface<T> for EagerRewriter<T> {
+    type Checkpoint = Self;
+
+    fn contents(self, _: &T) -> T::Buf {
         self.rewritten
     }
 
+    fn preview(&self, _: &T) -> T::Buf {
+        self.rewritten.clone()
+    }
+
     #[allow(clippy::cast_possible_wrap)]
-    fn rewrite(&mut self, start: usize, end: usize, replacement: &str) -> String {
+    fn rewrite(&mut self, _: &T, start: usize, end: usize, replacement: &T) -> T::Buf {
         let start = usize::try_from(start as isize + self.delta).unwrap();
         let end = usize::try_from(end as isize + self.delta).unwrap();
 
-        let prefix = &self.rewritten.as_bytes()[..start];
-        let replaced = &self.rewritten.as_bytes()[start..end];
-        let suffix = &self.rewritten.as_bytes()[end..];
-
-        let replaced = String::from_utf8(replaced.to_vec()).expect("`replaced` is not valid UTF-8");
+        let replaced = T::as_text(&self.rewritten).slice(start..end).to_buf();
 
-        self.rewritten = String::from_utf8(prefix.to_vec()).expect("`prefix` is not valid UTF-8")
-            + replacement
-            + std::str::from_utf8(suffix).expect("`suffix` is not valid UTF-8");
+        T::replace_range(&mut self.rewritten, start..end, replacement);
 
         self.delta += replacement.len() as isize - end as isize + start as isize;
 
         replaced
     }
+
+    fn checkpoint(&self) -> Self::Checkpoint {
+        self.clone()
+    }
+
+    fn rollback_to(&mut self, checkpoint: Self::Checkpoint) {
+        *self = checkpoint;
+    }
 }
@@ -1,27 +1,82 @@
 mod impls;
 
 use impls::LazyRewriter;
+use std::{fmt::Debug, ops::Range};
 
 #[cfg(feature = "check-rewrites")]
 use impls::EagerRewriter;
 
-pub trait Interface {
-    fn contents(self) -> String;
-    fn rewrite(&mut self, start: usize, end: usize, replacement: &str) -> String;
+/// A rewriter over byte offsets
+///
+/// The original text is passed to each method that needs it rather than stored.
+pub trait Interface<T: Text + ?Sized> {
+    type Checkpoint;
+    fn contents(self, original: &T) -> T::Buf;
+    fn preview(&self, original: &T) -> T::Buf;
+    fn rewrite(&mut self, original: &T, start: usize, end: usize, replacement: &T) -> T::Buf;
+    fn checkpoint(&self) -> Self::Checkpoint;
+    fn rollback_to(&mut self, checkpoint: Self::Checkpoint);
+}
+
+/// The kinds of text an [`OffsetBasedRewriter`] can rewrite, i.e., `str` and `[u8]`
+pub trait Text {
+    type Buf: Clone + Debug + Default + PartialEq;
+    fn len(&self) -> usize;
+    fn slice(&self, range: Range<usize>) -> &Self;
+    fn to_buf(&self) -> Self::Buf;
+    fn as_text(buf: &Self::Buf) -> &Self;
+    fn push(buf: &mut Self::Buf, text: &Self);
+    fn truncate(buf: &mut Self::Buf, len: usize);
+    /// Replaces `range` of `buf` with `replacement`
+    ///
+    /// Panics if `T` is `str` and either end of `range` is not on a character boundary.
+    fn replace_range(buf: &mut Self::Buf, range: Range<usize>, replacement: &Self);
 }
 
 #[derive(Debug)]
-pub struct OffsetBasedRewriter<'original> {
-    lazy: LazyRewriter<'original>,
+pub struct Checkpoint<T: Text + ?Sized = str> {
+    lazy: <LazyRewriter<T> as Interface<T>>::Checkpoint,
 
     #[cfg(feature = "check-rewrites")]
-    eager: EagerRewriter,
+    eager: <EagerRewriter<T> as Interface<T>>::Checkpoint,
+}
+
+#[derive(Debug)]
+pub struct OffsetBasedRewriter<T: Text + ?Sized = str> {
+    lazy: LazyRewriter<T>,
+
+    #[cfg(feature = "check-rewrites")]
+    eager: EagerRewriter<T>,
+}
+
+// smoelius: `Clone` is implemented manually because `derive` would require `T: Clone`.
+impl<T: Text + ?Sized> Clone for Checkpoint<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lazy: self.lazy,
+
+            #[cfg(feature = "check-rewrites")]
+            eager: self.eager.clone(),
+        }
+    }
+}
+
+impl<T: Text + ?Sized> Clone for OffsetBasedRewriter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            lazy: self.lazy.clone(),
+
+            #[cfg(feature = "check-rewrites")]
+            eager: self.eager.clone(),
+        }
+    }
 }
 
-impl<'original> OffsetBasedRewriter<'original> {
-    pub fn new(original: &'original str) -> Self {
+impl<T: Text + ?Sized> OffsetBasedRewriter<T> {
+    #[cfg_attr(not(feature = "check-rewrites"), allow(unused_variables))]
+    pub fn new(original: &T) -> Self {
         Self {
-            lazy: LazyRewriter::new(original),
+            lazy: LazyRewriter::new(),
 
             #[cfg(feature = "check-rewrites")]
             eager: EagerRewriter::new(original),
@@ -29,26 +84,124 @@ impl<'original> OffsetBasedRewriter<'original> {
     }
 }
 
-impl Interface for OffsetBasedRewriter<'_> {
+impl<T: Text + ?Sized> Interface<T> for OffsetBasedRewriter<T> {
+    type Checkpoint = Checkpoint<T>;
+
     #[allow(clippy::let_and_return)]
-    fn contents(self) -> String {
-        let contents = self.lazy.contents();
+    fn contents(self, original: &T) -> T::Buf {
+        let contents = self.lazy.contents(original);
 
         #[cfg(feature = "check-rewrites")]
         {
-            let contents_comparator = self.eager.contents();
+            let contents_comparator = self.eager.contents(original);
             assert_eq!(contents, contents_comparator);
         }
 
         contents
     }
 
-    fn rewrite(&mut self, start: usize, end: usize, replacement: &str) -> String {
-        let replaced = self.lazy.rewrite(start, end, replacement);
+    #[allow(clippy::let_and_return)]
+    fn preview(&self, original: &T) -> T::Buf {
+        let preview = self.lazy.preview(original);
+
+        #[cfg(feature = "check-rewrites")]
+        {
+            let preview_comparator = self.eager.preview(original);
+            assert_eq!(preview, preview_comparator);
+        }
+
+        preview
+    }
+
+    fn rewrite(&mut self, original: &T, start: usize, end: usize, replacement: &T) -> T::Buf {
+        let replaced = self.lazy.rewrite(original, start, end, replacement);
 
         #[cfg(feature = "check-rewrites")]
-        assert_eq!(replaced, self.eager.rewrite(start, end, replacement));
+        assert_eq!(
+            replaced,
+            self.eager.rewrite(original, start, end, replacement)
+        );
 
         replaced
     }
+
+    fn checkpoint(&self) -> Self::Checkpoint {
+        Checkpoint {
+            lazy: self.lazy.checkpoint(),
+
+            #[cfg(feature = "check-rewrites")]
+            eager: self.eager.checkpoint(),
+        }
+    }
+
+    fn rollback_to(&mut self, checkpoint: Self::Checkpoint) {
+        self.lazy.rollback_to(checkpoint.lazy);
+
+        #[cfg(feature = "check-rewrites")]
+        self.eager.rollback_to(checkpoint.eager);
+    }
+}
+
+impl Text for str {
+    type Buf = String;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn slice(&self, range: Range<usize>) -> &Self {
+        &self[range]
+    }
+
+    fn to_buf(&self) -> Self::Buf {
+        self.to_owned()
+    }
+
+    fn as_text(buf: &Self::Buf) -> &Self {
+        buf
+    }
+
+    fn push(buf: &mut Self::Buf, text: &Self) {
+        buf.push_str(text);
+    }
+
+    fn truncate(buf: &mut Self::Buf, len: usize) {
+        buf.truncate(len);
+    }
+
+    fn replace_range(buf: &mut Self::Buf, range: Range<usize>, replacement: &Self) {
+        buf.replace_range(range, replacement);
+    }
+}
+
+impl Text for [u8] {
+    type Buf = Vec<u8>;
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn slice(&self, range: Range<usize>) -> &Self {
+        &self[range]
+    }
+
+    fn to_buf(&self) -> Self::Buf {
+        self.to_vec()
+    }
+
+    fn as_text(buf: &Self::Buf) -> &Self {
+        buf
+    }
+
+    fn push(buf: &mut Self::Buf, text: &Self) {
+        buf.extend_from_slice(text);
+    }
+
+    fn truncate(buf: &mut Self::Buf, len: usize) {
+        buf.truncate(len);
+    }
+
+    fn replace_range(buf: &mut Self::Buf, range: Range<usize>, replacement: &Self) {
+        buf.splice(range, replacement.iter().copied());
+    }
 }
@@ -1,184 +1,401 @@
 use super::Interface;
 use crate::interface::{LineColumn, Span};
-use std::{
-    marker::PhantomData,
-    str::{Chars, Split},
-};
-
-#[derive(Debug)]
-pub struct CachingOffsetCalculator<'original, S: Span> {
-    line_history: Option<Vec<(usize, bool, &'original str)>>,
-    lines: Split<'original, char>,
-    chars: Option<Chars<'original>>,
+use crate::line_index::past_end;
+use crate::{LineIndex, LineTerminators, RewriteError, TextFormat};
+use std::marker::PhantomData;
+
+#[derive(Clone, Debug)]
+pub struct CachingOffsetCalculator<S: Span> {
+    /// If present, used to answer all queries, including ones in the past
+    line_index: Option<LineIndex>,
+    /// The start offset of the next line to read, or `None` if there are no more lines to read
+    next_line: Option<usize>,
+    /// The end offset of the current line, or `None` if the next line needs to be read
+    ///
+    /// When this is `Some`, the current line's remaining characters start at `offset`.
+    line_end: Option<usize>,
+    line_column: S::LineColumn,
+    offset: usize,
+    ascii: bool,
+    format: TextFormat,
+}
+
+/// The state of a [`CachingOffsetCalculator`], from which it can be restored
+#[derive(Clone, Debug)]
+pub struct Checkpoint<S: Span> {
+    next_line: Option<usize>,
+    line_end: Option<usize>,
     line_column: S::LineColumn,
     offset: usize,
     ascii: bool,
 }
 
-#[derive(Debug)]
-pub struct StatelessOffsetCalculator<'original, S: Span> {
-    original: &'original str,
+#[derive(Clone, Debug)]
+pub struct StatelessOffsetCalculator<S: Span> {
+    format: TextFormat,
     phantom: PhantomData<S>,
 }
 
-impl<'original, S: Span> CachingOffsetCalculator<'original, S> {
-    pub fn new(original: &'original str) -> Self {
-        let mut caching_offset_calculator = Self::without_line_history(original);
-        caching_offset_calculator.line_history = Some(Vec::new());
+impl<S: Span> CachingOffsetCalculator<S> {
+    pub fn new(original: &str, format: TextFormat) -> Self {
+        let mut caching_offset_calculator = Self::without_line_index(format);
+        caching_offset_calculator.line_index = Some(LineIndex::with_format(original, format));
         caching_offset_calculator
     }
 
-    pub fn without_line_history(original: &'original str) -> Self {
+    pub fn without_line_index(format: TextFormat) -> Self {
         Self {
-            line_history: None,
-            lines: original.split('\n'),
-            chars: None,
+            line_index: None,
+            next_line: Some(0),
+            line_end: None,
             line_column: S::line_column(1, 0),
             offset: 0,
             ascii: true,
+            format,
         }
     }
+
+    pub fn format(&self) -> TextFormat {
+        self.format
+    }
+
+    pub fn has_line_index(&self) -> bool {
+        self.line_index.is_some()
+    }
+
+    pub fn line_index(&self) -> Option<&LineIndex> {
+        self.line_index.as_ref()
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint<S> {
+        Checkpoint {
+            next_line: self.next_line,
+            line_end: self.line_end,
+            line_column: self.line_column,
+            offset: self.offset,
+            ascii: self.ascii,
+        }
+    }
+
+    pub fn restore(&mut self, checkpoint: &Checkpoint<S>) {
+        let Checkpoint {
+            next_line,
+            line_end,
+            line_column,
+            offset,
+            ascii,
+        } = *checkpoint;
+        self.next_line = next_line;
+        self.line_end = line_end;
+        self.line_column = line_column;
+        self.offset = offset;
+        self.ascii = ascii;
+    }
 }
 
-impl<'original, S: Span> StatelessOffsetCalculator<'original, S> {
+impl<S: Span> StatelessOffsetCalculator<S> {
     #[allow(dead_code)]
-    pub fn new(original: &'original str) -> Self {
+    pub fn new(format: TextFormat) -> Self {
         Self {
-            original,
+            format,
             phantom: PhantomData,
         }
     }
-}
 
-impl<S: Span> Interface<S> for CachingOffsetCalculator<'_, S> {
-    fn offset_from_line_column(&mut self, line_column: S::LineColumn) -> (usize, bool) {
-        if line_column < self.line_column {
-            let Some(lines_prev) = &self.line_history else {
-                panic!(
-                    "`offset_from_line_column` called on a `LineColumn` in the past: {:?} < {:?}",
-                    line_column, self.line_column
-                )
-            };
-
-            let (line_offset, line_ascii, line) = lines_prev[line_column.line() - 1];
+    #[allow(dead_code)]
+    pub fn line_column_from_offset(&self, original: &str, offset: usize) -> S::LineColumn {
+        let mut line_start = 0;
+        for (index, (line, terminator)) in split_lines(original, self.format.line_terminators)
+            .into_iter()
+            .enumerate()
+        {
+            let next_line = line_start + line.len() + terminator.len();
+            if offset < next_line || terminator.is_empty() {
+                let column = self
+                    .format
+                    .column_encoding
+                    .columns(line, (offset - line_start).min(line.len()));
+                return S::line_column(1 + index, column);
+            }
+            line_start = next_line;
+        }
 
-            // smoelius: It is okay to call `str::chars` here because the line is in the past.
-            #[allow(clippy::disallowed_methods)]
-            let (column_offset, column_ascii) =
-                advance_chars(&mut line.chars(), line_column.column());
+        unreachable!()
+    }
+}
 
-            return (line_offset + column_offset, line_ascii && column_ascii);
+impl<S: Span> Interface<S> for CachingOffsetCalculator<S> {
+    fn offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> (usize, bool) {
+        if let Some(line_index) = &self.line_index {
+            return line_index.offset_and_ascii(original, line_column);
         }
 
-        self.advance_to_line(line_column.line());
+        assert!(
+            self.line_column <= line_column,
+            "`offset_from_line_column` called on a `LineColumn` in the past: {:?} < {:?}",
+            line_column,
+            self.line_column
+        );
+
+        self.advance_to_line(original, line_column.line());
 
         let n_columns = line_column.column() - self.line_column.column();
 
         // smoelius: There could be no more characters to read, in which case the current `offset`
         // and `ascii` values are returned.
-        if let Some(chars) = self.chars_mut() {
-            let (offset, ascii) = advance_chars(chars, n_columns);
+        if let Some(line_end) = self.line_end_mut(original) {
+            let (offset, ascii, n) = self.format.column_encoding.advance(
+                &original[self.offset..line_end],
+                self.line_column.column(),
+                n_columns,
+            );
             self.offset += offset;
             self.ascii &= ascii;
 
-            *self.line_column.column_mut() = line_column.column();
+            // smoelius: `n` can be less than `n_columns` if the line is too short or the column
+            // falls inside a character. Either way, the next query resumes from `offset`.
+            *self.line_column.column_mut() += n;
         }
 
         (self.offset, self.ascii)
     }
+
+    fn try_offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> Result<(usize, bool), RewriteError<S::LineColumn>> {
+        let checkpoint = self.checkpoint();
+        let result = self.try_offset_from_line_column_unrestored(original, line_column);
+        if result.is_err() {
+            self.restore(&checkpoint);
+        }
+        result
+    }
 }
 
-impl<'original, S: Span> CachingOffsetCalculator<'original, S> {
-    fn advance_to_line(&mut self, line: usize) {
+impl<S: Span> CachingOffsetCalculator<S> {
+    /// Like [`Interface::try_offset_from_line_column`], but may leave `self` in an intermediate
+    /// state when an error is returned
+    fn try_offset_from_line_column_unrestored(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> Result<(usize, bool), RewriteError<S::LineColumn>> {
+        if line_column.line() == 0 {
+            return Err(RewriteError::LineOutOfBounds { line_column });
+        }
+
+        if let Some(line_index) = &self.line_index {
+            return line_index.try_offset_and_ascii(original, line_column);
+        }
+
+        assert!(
+            self.line_column <= line_column,
+            "`offset_from_line_column` called on a `LineColumn` in the past: {:?} < {:?}",
+            line_column,
+            self.line_column
+        );
+
+        self.advance_to_line(original, line_column.line());
+
+        if self.line_column.line() < line_column.line() {
+            return Err(RewriteError::LineOutOfBounds { line_column });
+        }
+
+        let n_columns = line_column.column() - self.line_column.column();
+
+        let Some(line_end) = self.line_end_mut(original) else {
+            return Err(RewriteError::LineOutOfBounds { line_column });
+        };
+
+        let (offset, ascii, n) = self.format.column_encoding.advance(
+            &original[self.offset..line_end],
+            self.line_column.column(),
+            n_columns,
+        );
+        self.offset += offset;
+        self.ascii &= ascii;
+        *self.line_column.column_mut() += n;
+
+        if n < n_columns && self.offset == line_end {
+            let last = self.next_line.is_none();
+            return Err(past_end(line_column, self.line_column.column(), last));
+        }
+
+        Ok((self.offset, self.ascii))
+    }
+
+    fn advance_to_line(&mut self, original: &str, line: usize) {
         if line <= self.line_column.line() {
             return;
         }
 
         // smoelius: Account for any remaining characters in the current line.
-        let Some(suffix) = self.chars_mut().map(Iterator::collect::<String>) else {
+        let Some(line_end) = self.line_end_mut(original) else {
             // smoelius: If there are no more characters to read, return.
             return;
         };
+        // smoelius: Ensure `line_end` is refilled the next time `line_end_mut` is called.
+        self.line_end = None;
 
-        // smoelius: Ensure `chars` is refilled the next time `chars_mut` is called.
-        self.chars = None;
-
-        self.offset += suffix.len() + 1;
-        self.ascii &= suffix.is_ascii();
+        self.skip_line(original, line_end);
         *self.line_column.line_mut() += 1;
         *self.line_column.column_mut() = 0;
 
         while self.line_column.line() < line {
-            let Some(line) = self.next_line() else {
+            let Some((_, line_end)) = self.next_line(original) else {
                 // smoelius: If there are no more lines to read, return.
                 return;
             };
 
-            self.offset += line.len() + 1;
-            self.ascii &= line.is_ascii();
+            self.skip_line(original, line_end);
             *self.line_column.line_mut() += 1;
             *self.line_column.column_mut() = 0;
         }
     }
 
-    /// Returns the contents of [`Self::chars`]
+    /// Advances `offset` past the remainder of the current line, which ends at `line_end`, and its
+    /// terminator
     ///
-    /// Calls [`Self::next_line`] if [`Self::chars`] is `None`. Returns `None` if there are no more
-    /// lines to read.
-    fn chars_mut(&mut self) -> Option<&mut Chars<'original>> {
-        #[allow(clippy::disallowed_methods)]
-        if self.chars.is_none() {
-            let line = self.next_line()?;
-            self.chars = Some(line.chars());
-        }
-        self.chars.as_mut()
+    /// If there is no next line, `offset` is advanced to one past the end of `original`.
+    fn skip_line(&mut self, original: &str, line_end: usize) {
+        let next_line = self.next_line.unwrap_or(line_end + 1);
+        // smoelius: The terminator need not be ASCII (e.g., U+2028).
+        self.ascii &= original[self.offset..next_line.min(original.len())].is_ascii();
+        self.offset = next_line;
     }
 
-    /// Fetches the next line from [`Self::lines`]
+    /// Returns the contents of [`Self::line_end`]
     ///
-    /// Returns `None` if there are no more lines to read.
-    fn next_line(&mut self) -> Option<&'original str> {
-        let line = self.lines.next()?;
-        if let Some(line_history) = &mut self.line_history {
-            line_history.push((self.offset, self.ascii, line));
+    /// Calls [`Self::next_line`] if [`Self::line_end`] is `None`. Returns `None` if there are no
+    /// more lines to read.
+    fn line_end_mut(&mut self, original: &str) -> Option<usize> {
+        if self.line_end.is_none() {
+            let (_, line_end) = self.next_line(original)?;
+            self.line_end = Some(line_end);
         }
-        Some(line)
+        self.line_end
     }
-}
 
-fn advance_chars(chars: &mut Chars, n: usize) -> (usize, bool) {
-    let prefix = chars.take(n).collect::<String>();
-    let offset = prefix.len();
-    let ascii = prefix.is_ascii();
-    (offset, ascii)
+    /// Fetches the start and end offsets of the next line
+    ///
+    /// Returns `None` if there are no more lines to read.
+    fn next_line(&mut self, original: &str) -> Option<(usize, usize)> {
+        let line_start = self.next_line?;
+        let terminator = self.format.line_terminators.find(&original[line_start..]);
+        let line_end = terminator.map_or(original.len(), |(index, _)| line_start + index);
+        self.next_line = terminator.map(|(_, len)| line_end + len);
+        Some((line_start, line_end))
+    }
 }
 
-#[allow(clippy::disallowed_methods)]
-impl<S: Span> Interface<S> for StatelessOffsetCalculator<'_, S> {
+impl<S: Span> Interface<S> for StatelessOffsetCalculator<S> {
     #[cfg_attr(
         dylint_lib = "misleading_variable_name",
         allow(misleading_variable_name)
     )]
-    fn offset_from_line_column(&mut self, line_column: S::LineColumn) -> (usize, bool) {
-        let mut lines = self.original.split('\n');
+    fn offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> (usize, bool) {
+        let mut lines = split_lines(original, self.format.line_terminators).into_iter();
         let mut offset = 0;
         let mut ascii = true;
 
         for _ in 1..line_column.line() {
-            let line = lines.next().unwrap();
-            offset += line.len() + 1;
-            ascii &= line.is_ascii();
+            let (line, terminator) = lines.next().unwrap();
+            offset += line.len() + terminator.len();
+            ascii &= line.is_ascii() && terminator.is_ascii();
         }
 
-        let prefix = lines
-            .next()
-            .unwrap()
-            .chars()
-            .take(line_column.column())
-            .collect::<String>();
-        offset += prefix.len();
-        ascii &= prefix.is_ascii();
+        let (line, _) = lines.next().unwrap();
+        let (prefix_len, prefix_ascii, _) =
+            self.format
+                .column_encoding
+                .advance(line, 0, line_column.column());
+        offset += prefix_len;
+        ascii &= prefix_ascii;
 
         (offset, ascii)
     }
+
+    fn try_offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> Result<(usize, bool), RewriteError<S::LineColumn>> {
+        let mut lines = split_lines(original, self.format.line_terminators).into_iter();
+        let mut offset = 0;
+        let mut ascii = true;
+
+        if line_column.line() == 0 {
+            return Err(RewriteError::LineOutOfBounds { line_column });
+        }
+
+        for _ in 1..line_column.line() {
+            let Some((line, terminator)) = lines.next() else {
+                return Err(RewriteError::LineOutOfBounds { line_column });
+            };
+            offset += line.len() + terminator.len();
+            ascii &= line.is_ascii() && terminator.is_ascii();
+        }
+
+        let Some((line, _)) = lines.next() else {
+            return Err(RewriteError::LineOutOfBounds { line_column });
+        };
+
+        let n = self.format.column_encoding.columns(line, line.len());
+        if n < line_column.column() {
+            return Err(past_end(line_column, n, lines.next().is_none()));
+        }
+
+        let (prefix_len, prefix_ascii, _) =
+            self.format
+                .column_encoding
+                .advance(line, 0, line_column.column());
+        offset += prefix_len;
+        ascii &= prefix_ascii;
+
+        Ok((offset, ascii))
+    }
+}
+
+/// Splits `original` into lines, each paired with its terminator
+///
+/// The last line's terminator is empty. Unlike [`LineTerminators::find`], this function examines
+/// one character at a time.
+#[allow(clippy::disallowed_methods, dead_code)]
+fn split_lines(original: &str, line_terminators: LineTerminators) -> Vec<(&str, &str)> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    let mut chars = original.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let is_terminator = match (line_terminators, c) {
+            (_, '\n')
+            | (LineTerminators::CrLfCrlf | LineTerminators::Unicode, '\r')
+            | (LineTerminators::Unicode, '\u{2028}' | '\u{2029}') => true,
+            (LineTerminators::LfCrlf, '\r') => chars.peek().is_some_and(|&(_, c)| c == '\n'),
+            _ => false,
+        };
+        if !is_terminator {
+            continue;
+        }
+        let mut terminator_end = index + c.len_utf8();
+        if c == '\r' && chars.next_if(|&(_, c)| c == '\n').is_some() {
+            terminator_end += 1;
+        }
+        lines.push((
+            &original[line_start..index],
+            &original[index..terminator_end],
+        ));
+        line_start = terminator_end;
+    }
+    lines.push((&original[line_start..], ""));
+    lines
 }
@@ -1,99 +1,442 @@
-use crate::SpanDefault;
-use crate::interface::Span;
-
-#[cfg(feature = "__check-proc-macro2-spans")]
-use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::interface::{FromLineColumns, Span};
+use crate::{ColumnEncoding, LineIndex, RewriteError, SpanDefault, TextFormat};
+use std::{ops::Range, sync::Arc};
 
 mod impls;
 
+mod shared;
+pub use shared::SharedOffsetCalculator;
+
 use impls::CachingOffsetCalculator;
 
+pub(crate) use impls::Checkpoint;
+
 #[cfg(feature = "check-offsets")]
 use impls::StatelessOffsetCalculator;
 
-#[cfg(feature = "__check-proc-macro2-spans")]
-static BASE_NEXT: AtomicUsize = AtomicUsize::new(0);
-
 pub trait Interface<S: Span> {
-    /// Returns the byte offset for `line_column`
+    /// Returns the byte offset in `original` for `line_column`
     ///
     /// The second component (the `bool`) indicates whether all characters up to the offset are
     /// ASCII.
-    fn offset_from_line_column(&mut self, line_column: S::LineColumn) -> (usize, bool);
+    fn offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> (usize, bool);
+
+    /// Like [`Interface::offset_from_line_column`], but returns an error if `line_column` is not
+    /// within `original`
+    ///
+    /// When an error is returned, the implementation's state is as it was before the call.
+    fn try_offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> Result<(usize, bool), RewriteError<S::LineColumn>>;
+}
+
+/// The operations a [`Rewriter`] needs from an offset calculator
+///
+/// [`Rewriter`]: crate::Rewriter
+pub(crate) trait SpanOffsets<S: Span> {
+    fn checkpoint(&self) -> Option<Checkpoint<S>>;
+    fn restore(&mut self, checkpoint: &Checkpoint<S>);
+    fn offsets_from_span(&mut self, original: &str, span: &S) -> (usize, usize);
+    fn try_offsets_from_span(
+        &mut self,
+        original: &str,
+        span: &S,
+    ) -> Result<(usize, usize), RewriteError<S::LineColumn>>;
+    fn line_column_from_offset(&self, original: &str, offset: usize) -> S::LineColumn;
+    fn format(&self) -> TextFormat;
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct OffsetCalculator<'original, S: Span = SpanDefault> {
-    caching: CachingOffsetCalculator<'original, S>,
+    original: &'original str,
+    pub(crate) state: State<S>,
+}
 
-    #[cfg(feature = "check-offsets")]
-    stateless: StatelessOffsetCalculator<'original, S>,
+/// Like [`OffsetCalculator`], but owns the original text
+///
+/// An `OwnedOffsetCalculator` can be stored in structs, returned from functions, and sent to other
+/// threads.
+#[derive(Clone, Debug)]
+pub struct OwnedOffsetCalculator<S: Span = SpanDefault> {
+    original: Arc<str>,
+    pub(crate) state: State<S>,
+}
+
+/// The state shared by [`OffsetCalculator`] and [`OwnedOffsetCalculator`]
+///
+/// The original text is passed to each method rather than stored.
+#[derive(Clone, Debug)]
+pub(crate) struct State<S: Span> {
+    caching: CachingOffsetCalculator<S>,
 
-    #[cfg(feature = "__check-proc-macro2-spans")]
-    base: usize,
+    #[cfg(feature = "check-offsets")]
+    stateless: StatelessOffsetCalculator<S>,
 }
 
 impl<'original, S: Span> OffsetCalculator<'original, S> {
+    /// Constructs an `OffsetCalculator` with a [`LineIndex`] of `original`, so that spans can be
+    /// converted in any order
+    ///
+    /// [`LineIndex`]: crate::LineIndex
     #[must_use]
     pub fn new(original: &'original str) -> Self {
-        Self::new_private(original, true)
+        Self::with_encoding(original, S::COLUMN_ENCODING)
+    }
+
+    /// Like [`OffsetCalculator::new`], but counts columns in `encoding`
+    #[must_use]
+    pub fn with_encoding(original: &'original str, encoding: ColumnEncoding) -> Self {
+        Self::with_format(original, encoding.into())
+    }
+
+    /// Like [`OffsetCalculator::new`], but counts lines and columns according to `format`
+    #[must_use]
+    pub fn with_format(original: &'original str, format: TextFormat) -> Self {
+        Self::new_private(original, true, format)
+    }
+
+    pub(crate) fn new_private(
+        original: &'original str,
+        with_line_index: bool,
+        format: TextFormat,
+    ) -> Self {
+        Self {
+            original,
+            state: State::new(original, with_line_index, format),
+        }
+    }
+
+    pub fn offsets_from_span(&mut self, span: &S) -> (usize, usize) {
+        self.state.offsets_from_span(self.original, span)
+    }
+
+    /// Like [`OffsetCalculator::offsets_from_span`], but returns an error rather than panicking or
+    /// clamping when `span` is reversed or not within the original text
+    ///
+    /// When an error is returned, the `OffsetCalculator`'s state is as it was before the call.
+    pub fn try_offsets_from_span(
+        &mut self,
+        span: &S,
+    ) -> Result<(usize, usize), RewriteError<S::LineColumn>> {
+        self.state.try_offsets_from_span(self.original, span)
+    }
+
+    /// Returns the `LineColumn` for byte offset `offset`
+    ///
+    /// Columns are counted in the calculator's [`ColumnEncoding`], which is characters by default,
+    /// as in [`Span`](crate::Span) and `proc_macro2::LineColumn`. An offset inside a character is
+    /// rounded down to the start of the character, and an offset within a line's terminator is
+    /// rounded down to the end of the line. Unlike [`OffsetCalculator::offsets_from_span`],
+    /// this method can be called with offsets in any order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the original text.
+    #[must_use]
+    pub fn line_column_from_offset(&self, offset: usize) -> S::LineColumn {
+        self.state.line_column_from_offset(self.original, offset)
+    }
+
+    /// Returns the span from byte offset `start` to byte offset `end`
+    ///
+    /// See [`OffsetCalculator::line_column_from_offset`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` or `end` is past the end of the original text.
+    #[must_use]
+    pub fn span_from_offsets(&self, start: usize, end: usize) -> S
+    where
+        S: FromLineColumns,
+    {
+        S::from_line_columns(
+            self.line_column_from_offset(start),
+            self.line_column_from_offset(end),
+        )
+    }
+}
+
+impl<S: Span> OwnedOffsetCalculator<S> {
+    /// Like [`OffsetCalculator::new`], but takes ownership of `original`
+    #[must_use]
+    pub fn new(original: impl Into<Arc<str>>) -> Self {
+        Self::with_encoding(original, S::COLUMN_ENCODING)
+    }
+
+    /// Like [`OffsetCalculator::with_encoding`], but takes ownership of `original`
+    #[must_use]
+    pub fn with_encoding(original: impl Into<Arc<str>>, encoding: ColumnEncoding) -> Self {
+        Self::with_format(original, encoding.into())
+    }
+
+    /// Like [`OffsetCalculator::with_format`], but takes ownership of `original`
+    #[must_use]
+    pub fn with_format(original: impl Into<Arc<str>>, format: TextFormat) -> Self {
+        Self::new_private(original.into(), true, format)
+    }
+
+    pub(crate) fn new_private(
+        original: Arc<str>,
+        with_line_index: bool,
+        format: TextFormat,
+    ) -> Self {
+        let state = State::new(&original, with_line_index, format);
+        Self { original, state }
+    }
+
+    #[must_use]
+    pub fn original(&self) -> &Arc<str> {
+        &self.original
+    }
+
+    pub fn offsets_from_span(&mut self, span: &S) -> (usize, usize) {
+        self.state.offsets_from_span(&self.original, span)
     }
 
-    pub(crate) fn new_private(original: &'original str, with_line_history: bool) -> Self {
-        let caching = if with_line_history {
-            CachingOffsetCalculator::new(original)
+    /// Like [`OwnedOffsetCalculator::offsets_from_span`], but returns an error rather than
+    /// panicking or clamping when `span` is reversed or not within the original text
+    ///
+    /// When an error is returned, the `OwnedOffsetCalculator`'s state is as it was before the
+    /// call.
+    pub fn try_offsets_from_span(
+        &mut self,
+        span: &S,
+    ) -> Result<(usize, usize), RewriteError<S::LineColumn>> {
+        self.state.try_offsets_from_span(&self.original, span)
+    }
+
+    /// Returns the `LineColumn` for byte offset `offset`
+    ///
+    /// See [`OffsetCalculator::line_column_from_offset`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the original text.
+    #[must_use]
+    pub fn line_column_from_offset(&self, offset: usize) -> S::LineColumn {
+        self.state.line_column_from_offset(&self.original, offset)
+    }
+
+    /// Returns the span from byte offset `start` to byte offset `end`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` or `end` is past the end of the original text.
+    #[must_use]
+    pub fn span_from_offsets(&self, start: usize, end: usize) -> S
+    where
+        S: FromLineColumns,
+    {
+        S::from_line_columns(
+            self.line_column_from_offset(start),
+            self.line_column_from_offset(end),
+        )
+    }
+}
+
+impl<S: Span> State<S> {
+    fn new(original: &str, with_line_index: bool, format: TextFormat) -> Self {
+        let caching = if with_line_index {
+            CachingOffsetCalculator::new(original, format)
         } else {
-            CachingOffsetCalculator::without_line_history(original)
+            CachingOffsetCalculator::without_line_index(format)
         };
 
         Self {
             caching,
 
             #[cfg(feature = "check-offsets")]
-            stateless: StatelessOffsetCalculator::new(original),
+            stateless: StatelessOffsetCalculator::new(format),
+        }
+    }
+
+    pub(crate) fn has_line_index(&self) -> bool {
+        self.caching.has_line_index()
+    }
 
-            #[cfg(feature = "__check-proc-macro2-spans")]
-            base: BASE_NEXT.fetch_add(1 + original.len(), Ordering::SeqCst),
+    pub(crate) fn line_column_from_offset(&self, original: &str, offset: usize) -> S::LineColumn {
+        // smoelius: Calculators without a `LineIndex` are constructed only by `Rewriter::new`,
+        // which does not expose them. So building a `LineIndex` here should be rare.
+        match self.caching.line_index() {
+            Some(line_index) => line_column_from_offset::<S>(line_index, original, offset),
+            None => line_column_from_offset::<S>(
+                &LineIndex::with_format(original, self.caching.format()),
+                original,
+                offset,
+            ),
         }
     }
+}
+
+fn line_column_from_offset<S: Span>(
+    line_index: &LineIndex,
+    original: &str,
+    offset: usize,
+) -> S::LineColumn {
+    let Some((line, column)) = line_index.line_and_column(original, offset) else {
+        panic!(
+            "offset {offset} is past the end of the original text, whose length is {}",
+            original.len()
+        );
+    };
+
+    let line_column = S::line_column(line, column);
+
+    #[cfg(feature = "check-offsets")]
+    {
+        let line_column_comparator = StatelessOffsetCalculator::<S>::new(line_index.format())
+            .line_column_from_offset(original, offset);
+        assert_eq!(line_column, line_column_comparator, "failed for {offset}");
+    }
 
-    pub fn offsets_from_span(&mut self, span: &S) -> (usize, usize) {
-        let (start, start_ascii) = self.offset_from_line_column(span.start());
-        let (end, end_ascii) = self.offset_from_line_column(span.end());
+    line_column
+}
 
-        assert!(!end_ascii || start_ascii);
+impl<S: Span> SpanOffsets<S> for State<S> {
+    /// Returns a [`Checkpoint`] of the calculator's state, if one is needed to revisit past
+    /// positions
+    ///
+    /// A calculator with a [`LineIndex`] can revisit past positions on its own, so `None` is
+    /// returned for such calculators.
+    ///
+    /// [`LineIndex`]: crate::LineIndex
+    fn checkpoint(&self) -> Option<Checkpoint<S>> {
+        if self.has_line_index() {
+            None
+        } else {
+            Some(self.caching.checkpoint())
+        }
+    }
+
+    fn restore(&mut self, checkpoint: &Checkpoint<S>) {
+        self.caching.restore(checkpoint);
+    }
 
-        // smoelius: `proc_macro2::Span`'s debug output doesn't seem to account for UTF-8.
-        #[cfg(feature = "__check-proc-macro2-spans")]
-        if end_ascii {
-            let start = self.base + start;
-            let end = self.base + end;
-            assert_eq!(
-                format!("{span:?}"),
-                format!("bytes({}..{})", 1 + start, 1 + end),
-                "self = {:#?}, span.start() = {:?}, span.end() = {:?}",
-                self,
-                span.start(),
-                span.end(),
-            );
+    fn offsets_from_span(&mut self, original: &str, span: &S) -> (usize, usize) {
+        if let Some(offsets) = byte_offsets(original, self.caching.format(), span) {
+            return offsets;
         }
 
+        let (start, start_ascii) = self.offset_from_line_column(original, span.start());
+        let (end, end_ascii) = self.offset_from_line_column(original, span.end());
+
+        assert!(!end_ascii || start_ascii);
+
         (start, end)
     }
+
+    fn try_offsets_from_span(
+        &mut self,
+        original: &str,
+        span: &S,
+    ) -> Result<(usize, usize), RewriteError<S::LineColumn>> {
+        if span.end() < span.start() {
+            return Err(RewriteError::ReversedSpan {
+                start: span.start(),
+                end: span.end(),
+            });
+        }
+
+        if let Some(offsets) = byte_offsets(original, self.caching.format(), span) {
+            return Ok(offsets);
+        }
+
+        let checkpoint = self.caching.checkpoint();
+
+        let (start, _) = self.try_offset_from_line_column(original, span.start())?;
+        let (end, _) = match self.try_offset_from_line_column(original, span.end()) {
+            Ok(pair) => pair,
+            Err(error) => {
+                self.caching.restore(&checkpoint);
+                return Err(error);
+            }
+        };
+
+        Ok((start, end))
+    }
+
+    fn line_column_from_offset(&self, original: &str, offset: usize) -> S::LineColumn {
+        State::line_column_from_offset(self, original, offset)
+    }
+
+    fn format(&self) -> TextFormat {
+        self.caching.format()
+    }
 }
 
-impl<S: Span> Interface<S> for OffsetCalculator<'_, S> {
-    fn offset_from_line_column(&mut self, line_column: S::LineColumn) -> (usize, bool) {
-        let (offset, ascii) = self.caching.offset_from_line_column(line_column);
+/// Returns `span`'s byte offsets in `original`, if they are known without resolving its lines and
+/// columns
+///
+/// `None` is returned if `format` is not how `S` counts lines and columns, or if the offsets are
+/// not character boundaries within `original`. In either case, the caller falls back to resolving
+/// the span's lines and columns.
+fn byte_offsets<S: Span>(original: &str, format: TextFormat, span: &S) -> Option<(usize, usize)> {
+    if format != TextFormat::from(S::COLUMN_ENCODING) {
+        return None;
+    }
+
+    let Range { start, end } = span.byte_range()?;
+
+    original.get(start..end)?;
+
+    #[cfg(feature = "check-offsets")]
+    {
+        let mut stateless = StatelessOffsetCalculator::<S>::new(format);
+        let (start_comparator, _) = stateless.offset_from_line_column(original, span.start());
+        let (end_comparator, _) = stateless.offset_from_line_column(original, span.end());
+        assert_eq!(
+            (start, end),
+            (start_comparator, end_comparator),
+            "failed for {span:?}"
+        );
+    }
+
+    Some((start, end))
+}
+
+impl<S: Span> Interface<S> for State<S> {
+    fn offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> (usize, bool) {
+        let (offset, ascii) = self.caching.offset_from_line_column(original, line_column);
 
         #[cfg(feature = "check-offsets")]
         {
-            let (offset_comparator, ascii_comparator) =
-                self.stateless.offset_from_line_column(line_column);
+            let (offset_comparator, ascii_comparator) = self
+                .stateless
+                .offset_from_line_column(original, line_column);
             assert_eq!(offset, offset_comparator, "failed for {line_column:?}");
             assert_eq!(ascii, ascii_comparator, "failed for {line_column:?}");
         }
 
         (offset, ascii)
     }
+
+    fn try_offset_from_line_column(
+        &mut self,
+        original: &str,
+        line_column: S::LineColumn,
+    ) -> Result<(usize, bool), RewriteError<S::LineColumn>> {
+        let result = self
+            .caching
+            .try_offset_from_line_column(original, line_column);
+
+        #[cfg(feature = "check-offsets")]
+        {
+            let result_comparator = self
+                .stateless
+                .try_offset_from_line_column(original, line_column);
+            assert_eq!(result, result_comparator, "failed for {line_column:?}");
+        }
+
+        result
+    }
 }
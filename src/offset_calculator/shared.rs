@@ -0,0 +1,191 @@
+use super::{Checkpoint, SpanOffsets};
+use crate::interface::{FromLineColumns, Span};
+use crate::{ColumnEncoding, LineIndex, RewriteError, SpanDefault, TextFormat};
+use std::{marker::PhantomData, sync::Arc};
+
+#[cfg(feature = "check-offsets")]
+use super::{Interface, impls::StatelessOffsetCalculator};
+
+/// An immutable offset calculator that can be queried from several threads at once
+///
+/// A `SharedOffsetCalculator` builds a [`LineIndex`] of its original text when it is constructed.
+/// Its queries take `&self`, so an `Arc<SharedOffsetCalculator>` can be passed to [`Rewriter`]s
+/// and [`OwnedRewriter`]s on different threads without the text being rescanned.
+///
+/// [`OwnedRewriter`]: crate::OwnedRewriter
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Debug)]
+pub struct SharedOffsetCalculator<S: Span = SpanDefault> {
+    original: Arc<str>,
+    line_index: LineIndex,
+
+    // smoelius: `fn() -> S` rather than `S` so that the calculator is `Send` and `Sync` even when
+    // `S` is not (e.g., `proc_macro2::Span`).
+    phantom: PhantomData<fn() -> S>,
+}
+
+impl<S: Span> SharedOffsetCalculator<S> {
+    #[must_use]
+    pub fn new(original: impl Into<Arc<str>>) -> Self {
+        Self::with_encoding(original, S::COLUMN_ENCODING)
+    }
+
+    /// Like [`SharedOffsetCalculator::new`], but counts columns in `encoding`
+    #[must_use]
+    pub fn with_encoding(original: impl Into<Arc<str>>, encoding: ColumnEncoding) -> Self {
+        Self::with_format(original, encoding.into())
+    }
+
+    /// Like [`SharedOffsetCalculator::new`], but counts lines and columns according to `format`
+    #[must_use]
+    pub fn with_format(original: impl Into<Arc<str>>, format: TextFormat) -> Self {
+        let original = original.into();
+        let line_index = LineIndex::with_format(&original, format);
+
+        Self {
+            original,
+            line_index,
+            phantom: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn original(&self) -> &Arc<str> {
+        &self.original
+    }
+
+    #[must_use]
+    pub fn line_index(&self) -> &LineIndex {
+        &self.line_index
+    }
+
+    pub fn offsets_from_span(&self, span: &S) -> (usize, usize) {
+        if let Some(offsets) = super::byte_offsets(&self.original, self.line_index.format(), span) {
+            return offsets;
+        }
+
+        let (start, start_ascii) = self.offset_from_line_column(span.start());
+        let (end, end_ascii) = self.offset_from_line_column(span.end());
+
+        assert!(!end_ascii || start_ascii);
+
+        (start, end)
+    }
+
+    /// Like [`SharedOffsetCalculator::offsets_from_span`], but returns an error rather than
+    /// panicking or clamping when `span` is reversed or not within the original text
+    pub fn try_offsets_from_span(
+        &self,
+        span: &S,
+    ) -> Result<(usize, usize), RewriteError<S::LineColumn>> {
+        if span.end() < span.start() {
+            return Err(RewriteError::ReversedSpan {
+                start: span.start(),
+                end: span.end(),
+            });
+        }
+
+        if let Some(offsets) = super::byte_offsets(&self.original, self.line_index.format(), span) {
+            return Ok(offsets);
+        }
+
+        let (start, _) = self.try_offset_from_line_column(span.start())?;
+        let (end, _) = self.try_offset_from_line_column(span.end())?;
+
+        Ok((start, end))
+    }
+
+    /// Returns the `LineColumn` for byte offset `offset`
+    ///
+    /// See [`OffsetCalculator::line_column_from_offset`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` is past the end of the original text.
+    ///
+    /// [`OffsetCalculator::line_column_from_offset`]: crate::OffsetCalculator::line_column_from_offset
+    #[must_use]
+    pub fn line_column_from_offset(&self, offset: usize) -> S::LineColumn {
+        super::line_column_from_offset::<S>(&self.line_index, &self.original, offset)
+    }
+
+    /// Returns the span from byte offset `start` to byte offset `end`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start` or `end` is past the end of the original text.
+    #[must_use]
+    pub fn span_from_offsets(&self, start: usize, end: usize) -> S
+    where
+        S: FromLineColumns,
+    {
+        S::from_line_columns(
+            self.line_column_from_offset(start),
+            self.line_column_from_offset(end),
+        )
+    }
+
+    fn offset_from_line_column(&self, line_column: S::LineColumn) -> (usize, bool) {
+        let (offset, ascii) = self
+            .line_index
+            .offset_and_ascii(&self.original, line_column);
+
+        #[cfg(feature = "check-offsets")]
+        {
+            let (offset_comparator, ascii_comparator) =
+                StatelessOffsetCalculator::<S>::new(self.line_index.format())
+                    .offset_from_line_column(&self.original, line_column);
+            assert_eq!(offset, offset_comparator, "failed for {line_column:?}");
+            assert_eq!(ascii, ascii_comparator, "failed for {line_column:?}");
+        }
+
+        (offset, ascii)
+    }
+
+    fn try_offset_from_line_column(
+        &self,
+        line_column: S::LineColumn,
+    ) -> Result<(usize, bool), RewriteError<S::LineColumn>> {
+        let result = self
+            .line_index
+            .try_offset_and_ascii(&self.original, line_column);
+
+        #[cfg(feature = "check-offsets")]
+        {
+            let result_comparator = StatelessOffsetCalculator::<S>::new(self.line_index.format())
+                .try_offset_from_line_column(&self.original, line_column);
+            assert_eq!(result, result_comparator, "failed for {line_column:?}");
+        }
+
+        result
+    }
+}
+
+impl<S: Span> SpanOffsets<S> for &SharedOffsetCalculator<S> {
+    // smoelius: A `SharedOffsetCalculator` has no state to restore.
+    fn checkpoint(&self) -> Option<Checkpoint<S>> {
+        None
+    }
+
+    fn restore(&mut self, _: &Checkpoint<S>) {}
+
+    fn offsets_from_span(&mut self, _: &str, span: &S) -> (usize, usize) {
+        SharedOffsetCalculator::offsets_from_span(self, span)
+    }
+
+    fn try_offsets_from_span(
+        &mut self,
+        _: &str,
+        span: &S,
+    ) -> Result<(usize, usize), RewriteError<S::LineColumn>> {
+        SharedOffsetCalculator::try_offsets_from_span(self, span)
+    }
+
+    fn line_column_from_offset(&self, _: &str, offset: usize) -> S::LineColumn {
+        SharedOffsetCalculator::line_column_from_offset(self, offset)
+    }
+
+    fn format(&self) -> TextFormat {
+        self.line_index.format()
+    }
+}
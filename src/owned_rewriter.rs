@@ -0,0 +1,200 @@
+use crate::diff::Diff;
+use crate::interface::Span;
+use crate::offset_calculator::{OwnedOffsetCalculator, SharedOffsetCalculator, SpanOffsets};
+use crate::rewriter::State;
+use crate::source_map::SourceMap;
+use crate::{
+    ByteSpan, Checkpoint, ColumnEncoding, LineEndingPolicy, RewriteError, SpanDefault, TextFormat,
+};
+use std::sync::Arc;
+
+/// Like [`Rewriter`], but owns the original text
+///
+/// An `OwnedRewriter` has no lifetime parameter. So it can be stored in structs, returned from
+/// functions that read the original text themselves, and sent to other threads.
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Clone, Debug)]
+pub struct OwnedRewriter<S: Span = SpanDefault> {
+    original: Arc<str>,
+    offset_calculator: Calculator<S>,
+    state: State<S>,
+}
+
+#[derive(Clone, Debug)]
+enum Calculator<S: Span> {
+    Owned(OwnedOffsetCalculator<S>),
+    Shared(Arc<SharedOffsetCalculator<S>>),
+}
+
+impl<S: Span> OwnedRewriter<S> {
+    #[must_use]
+    pub fn new(original: impl Into<Arc<str>>) -> Self {
+        Self::with_encoding(original, S::COLUMN_ENCODING)
+    }
+
+    /// Like [`OwnedRewriter::new`], but interprets spans' columns in `encoding`
+    #[must_use]
+    pub fn with_encoding(original: impl Into<Arc<str>>, encoding: ColumnEncoding) -> Self {
+        Self::with_format(original, encoding.into())
+    }
+
+    /// Like [`OwnedRewriter::new`], but interprets spans' lines and columns according to `format`
+    #[must_use]
+    pub fn with_format(original: impl Into<Arc<str>>, format: TextFormat) -> Self {
+        let offset_calculator = OwnedOffsetCalculator::new_private(original.into(), false, format);
+        Self::with_offset_calculator(offset_calculator)
+    }
+
+    /// Constructs an `OwnedRewriter` for the original text of `offset_calculator`
+    #[must_use]
+    pub fn with_offset_calculator(offset_calculator: OwnedOffsetCalculator<S>) -> Self {
+        let original = offset_calculator.original().clone();
+        Self {
+            state: State::new(&original, offset_calculator.state.format()),
+            original,
+            offset_calculator: Calculator::Owned(offset_calculator),
+        }
+    }
+
+    /// Constructs an `OwnedRewriter` for the original text of `offset_calculator`
+    ///
+    /// Unlike an [`OwnedOffsetCalculator`], a [`SharedOffsetCalculator`] can be shared by
+    /// `OwnedRewriter`s on different threads.
+    #[must_use]
+    pub fn with_shared_offset_calculator(
+        offset_calculator: Arc<SharedOffsetCalculator<S>>,
+    ) -> Self {
+        let original = offset_calculator.original().clone();
+        Self {
+            state: State::new(&original, offset_calculator.line_index().format()),
+            original,
+            offset_calculator: Calculator::Shared(offset_calculator),
+        }
+    }
+
+    #[must_use]
+    pub fn original(&self) -> &Arc<str> {
+        &self.original
+    }
+
+    /// See [`Rewriter::set_line_ending_policy`].
+    ///
+    /// [`Rewriter::set_line_ending_policy`]: crate::Rewriter::set_line_ending_policy
+    pub fn set_line_ending_policy(&mut self, policy: LineEndingPolicy) {
+        self.state.set_line_ending_policy(&self.original, policy);
+    }
+
+    /// See [`Rewriter::set_source_span`].
+    ///
+    /// [`Rewriter::set_source_span`]: crate::Rewriter::set_source_span
+    pub fn set_source_span(&mut self, span: S) {
+        self.state.set_source_span(span);
+    }
+
+    pub fn contents(self) -> String {
+        self.state.contents(&self.original)
+    }
+
+    /// Returns the contents as they would be if [`OwnedRewriter::contents`] were called now,
+    /// without consuming the `OwnedRewriter`
+    #[must_use]
+    pub fn preview(&self) -> String {
+        self.state.preview(&self.original)
+    }
+
+    /// Like [`OwnedRewriter::contents`], but also returns a [`SourceMap`] relating the original and
+    /// rewritten texts
+    pub fn contents_with_source_map(self) -> (String, SourceMap<S>) {
+        self.state.contents_with_source_map(&self.original)
+    }
+
+    /// Returns a [`Checkpoint`] of the `OwnedRewriter`'s current state
+    ///
+    /// See [`Rewriter::checkpoint`].
+    ///
+    /// [`Rewriter::checkpoint`]: crate::Rewriter::checkpoint
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint<S> {
+        match &self.offset_calculator {
+            Calculator::Owned(offset_calculator) => self.state.checkpoint(&offset_calculator.state),
+            Calculator::Shared(offset_calculator) => self.state.checkpoint(&&**offset_calculator),
+        }
+    }
+
+    /// Undoes the rewrites performed since `checkpoint` was taken
+    ///
+    /// # Panics
+    ///
+    /// Panics if `checkpoint` was taken after the `OwnedRewriter`'s current state.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint<S>) {
+        self.offset_calculator.with_mut(|offset_calculator| {
+            self.state.rollback_to(offset_calculator, checkpoint);
+        });
+    }
+
+    /// Returns a unified diff of the rewrites performed so far, with `context` lines of context
+    ///
+    /// See [`Rewriter::diff`].
+    ///
+    /// [`Rewriter::diff`]: crate::Rewriter::diff
+    #[must_use]
+    pub fn diff(&self, path_label: &str, context: usize) -> Diff {
+        self.state.diff(&self.original, path_label, context)
+    }
+
+    /// See [`Rewriter::rewrite`], including its panics.
+    ///
+    /// [`Rewriter::rewrite`]: crate::Rewriter::rewrite
+    pub fn rewrite(&mut self, span: &S, replacement: &str) -> String {
+        self.offset_calculator.with_mut(|offset_calculator| {
+            self.state
+                .rewrite(&self.original, offset_calculator, span, replacement)
+        })
+    }
+
+    /// Like [`OwnedRewriter::rewrite`], but returns an error rather than panicking or clamping
+    /// when `span` is out of order, reversed, or not within the original text
+    ///
+    /// When an error is returned, no rewrite is performed and the `OwnedRewriter` can continue to
+    /// be used.
+    pub fn try_rewrite(
+        &mut self,
+        span: &S,
+        replacement: &str,
+    ) -> Result<String, RewriteError<S::LineColumn>> {
+        self.offset_calculator.with_mut(|offset_calculator| {
+            self.state
+                .try_rewrite(&self.original, offset_calculator, span, replacement)
+        })
+    }
+
+    /// See [`Rewriter::rewrite_bytes`].
+    ///
+    /// [`Rewriter::rewrite_bytes`]: crate::Rewriter::rewrite_bytes
+    pub fn rewrite_bytes(&mut self, span: impl Into<ByteSpan>, replacement: &str) -> String {
+        self.state
+            .rewrite_bytes(&self.original, span.into(), replacement)
+    }
+
+    /// See [`Rewriter::try_rewrite_bytes`].
+    ///
+    /// [`Rewriter::try_rewrite_bytes`]: crate::Rewriter::try_rewrite_bytes
+    pub fn try_rewrite_bytes(
+        &mut self,
+        span: impl Into<ByteSpan>,
+        replacement: &str,
+    ) -> Result<String, RewriteError<usize>> {
+        self.state
+            .try_rewrite_bytes(&self.original, span.into(), replacement)
+    }
+}
+
+impl<S: Span> Calculator<S> {
+    fn with_mut<R>(&mut self, f: impl FnOnce(&mut dyn SpanOffsets<S>) -> R) -> R {
+        match self {
+            Self::Owned(offset_calculator) => f(&mut offset_calculator.state),
+            Self::Shared(offset_calculator) => f(&mut &**offset_calculator),
+        }
+    }
+}
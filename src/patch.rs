@@ -0,0 +1,364 @@
+//! Parsing and applying unified diffs
+//!
+//! [`parse`] splits a unified diff into one [`Patch`] per file. [`Patch::apply`] checks each hunk's
+//! context and removed lines against the original text and applies the hunks that match using a
+//! [`Rewriter`]. Hunks that do not match are returned as [`Rejected`] hunks.
+
+use crate::diff::{Hunk, Line};
+use crate::{LineColumn, OffsetCalculator, RewriteError, Rewriter, Span};
+use std::{
+    cell::RefCell,
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+/// The hunks of a unified diff that apply to one file
+///
+/// The paths are as they appear in the `---` and `+++` lines, e.g., with any `a/` or `b/` prefix.
+/// They are `None` if the diff has no such lines.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Patch {
+    pub original_path: Option<String>,
+    pub rewritten_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// The result of [`Patch::apply`]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Applied {
+    pub contents: String,
+    pub rejected: Vec<Rejected>,
+}
+
+/// A hunk that [`Patch::apply`] could not apply
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rejected {
+    /// The index of the hunk in [`Patch::hunks`]
+    pub index: usize,
+    pub hunk: Hunk,
+    pub reason: Reason,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Reason {
+    /// The hunk's context and removed lines do not match the original text at `span`
+    ContextMismatch { span: Span },
+    /// The hunk's lines are not within the original text, or the hunk overlaps an earlier one
+    Rewrite(RewriteError<LineColumn>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseError {
+    /// A `@@` line could not be parsed
+    MalformedHunkHeader { line: usize },
+    /// A line within a hunk does not start with ` `, `-`, `+`, or `\`
+    UnexpectedLine { line: usize },
+    /// The diff ended before the last hunk's line counts were satisfied
+    UnexpectedEof { line: usize },
+}
+
+/// The parts of a hunk needed to check and apply it
+struct HunkEdit {
+    /// The span of the hunk's context and removed lines
+    region: Span,
+    /// The text of the hunk's context and removed lines
+    expected: String,
+    /// The span of the hunk's removed lines
+    span: Span,
+    /// The text of the hunk's added lines
+    replacement: String,
+}
+
+/// Parses `text` as a unified diff
+///
+/// Lines outside of hunks that are not `---` or `+++` lines (e.g., `diff --git` lines) are ignored.
+pub fn parse(text: &str) -> Result<Vec<Patch>, ParseError> {
+    let mut patches = Vec::<Patch>::new();
+    let mut lines = text.split_inclusive('\n').enumerate().peekable();
+
+    while let Some((index, line)) = lines.next() {
+        if let Some(path) = line.strip_prefix("--- ") {
+            patches.push(Patch {
+                original_path: Some(parse_path(path)),
+                rewritten_path: None,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+
+        if let Some(path) = line.strip_prefix("+++ ")
+            && let Some(patch) = patches.last_mut()
+            && patch.rewritten_path.is_none()
+            && patch.hunks.is_empty()
+        {
+            patch.rewritten_path = Some(parse_path(path));
+            continue;
+        }
+
+        if !line.starts_with("@@ ") {
+            continue;
+        }
+
+        let mut hunk =
+            parse_hunk_header(line).ok_or(ParseError::MalformedHunkHeader { line: index + 1 })?;
+
+        let mut n_original = 0;
+        let mut n_rewritten = 0;
+        while n_original < hunk.original_len || n_rewritten < hunk.rewritten_len {
+            let Some((index, line)) = lines.next() else {
+                return Err(ParseError::UnexpectedEof {
+                    line: text.split_inclusive('\n').count(),
+                });
+            };
+            // smoelius: Some tools strip the trailing space from empty context lines.
+            let line = if line == "\n" { " \n" } else { line };
+            // smoelius: `line` is sliced only after its first byte is known to be ASCII, so that a
+            // multibyte first character results in an error rather than a panic.
+            let rest = || line[1..].to_owned();
+            match line.as_bytes()[0] {
+                b' ' => {
+                    hunk.lines.push(Line::Context(rest()));
+                    n_original += 1;
+                    n_rewritten += 1;
+                }
+                b'-' => {
+                    hunk.lines.push(Line::Removed(rest()));
+                    n_original += 1;
+                }
+                b'+' => {
+                    hunk.lines.push(Line::Added(rest()));
+                    n_rewritten += 1;
+                }
+                b'\\' => strip_newline(&mut hunk),
+                _ => return Err(ParseError::UnexpectedLine { line: index + 1 }),
+            }
+            if hunk.original_len < n_original || hunk.rewritten_len < n_rewritten {
+                return Err(ParseError::UnexpectedLine { line: index + 1 });
+            }
+        }
+
+        // smoelius: A "\ No newline at end of file" line may follow the hunk's last line.
+        if let Some((_, line)) = lines.peek()
+            && line.starts_with('\\')
+        {
+            strip_newline(&mut hunk);
+            lines.next();
+        }
+
+        if patches.is_empty() {
+            patches.push(Patch {
+                original_path: None,
+                rewritten_path: None,
+                hunks: Vec::new(),
+            });
+        }
+
+        patches.last_mut().unwrap().hunks.push(hunk);
+    }
+
+    Ok(patches)
+}
+
+impl Patch {
+    /// Returns the span of each hunk's removed lines and the text of its added lines
+    ///
+    /// Spans refer to the original text and do not include the hunks' context lines. The edits
+    /// are in the order of the hunks, and can be applied with an [`EditSet`].
+    ///
+    /// [`EditSet`]: crate::EditSet
+    #[must_use]
+    pub fn edits(&self) -> Vec<(Span, String)> {
+        self.hunks
+            .iter()
+            .map(|hunk| {
+                let HunkEdit {
+                    span, replacement, ..
+                } = hunk_edit(hunk);
+                (span, replacement)
+            })
+            .collect()
+    }
+
+    /// Applies the patch's hunks to `original`
+    ///
+    /// A hunk is applied only if its context and removed lines match the original text exactly,
+    /// and it starts after the previously applied hunk ends.
+    #[must_use]
+    pub fn apply(&self, original: &str) -> Applied {
+        let offset_calculator = Rc::new(RefCell::new(OffsetCalculator::new(original)));
+        let mut rewriter = Rewriter::with_offset_calculator(original, offset_calculator.clone());
+        let mut rejected = Vec::new();
+
+        for (index, hunk) in self.hunks.iter().enumerate() {
+            let HunkEdit {
+                region,
+                expected,
+                span,
+                replacement,
+            } = hunk_edit(hunk);
+
+            let offsets = offset_calculator
+                .borrow_mut()
+                .try_offsets_from_span(&region);
+
+            let result = offsets
+                .map_err(Reason::Rewrite)
+                .and_then(|(start, end)| {
+                    if original[start..end] == expected {
+                        Ok(())
+                    } else {
+                        Err(Reason::ContextMismatch { span: region })
+                    }
+                })
+                .and_then(|()| {
+                    rewriter
+                        .try_rewrite(&span, &replacement)
+                        .map_err(Reason::Rewrite)
+                });
+
+            if let Err(reason) = result {
+                rejected.push(Rejected {
+                    index,
+                    hunk: hunk.clone(),
+                    reason,
+                });
+            }
+        }
+
+        Applied {
+            contents: rewriter.contents(),
+            rejected,
+        }
+    }
+}
+
+fn hunk_edit(hunk: &Hunk) -> HunkEdit {
+    // smoelius: When `original_len` is 0, `original_start` is the line after which the hunk's
+    // lines are inserted.
+    let first_line = if hunk.original_len == 0 {
+        hunk.original_start + 1
+    } else {
+        hunk.original_start
+    };
+
+    let mut line_column = LineColumn {
+        line: first_line,
+        column: 0,
+    };
+    let region_start = line_column;
+    let mut span: Option<(LineColumn, LineColumn)> = None;
+    let mut expected = String::new();
+    let mut replacement = String::new();
+
+    // smoelius: Context lines that follow a removed or added line. They become part of the
+    // replacement only if another removed or added line follows them.
+    let mut pending = String::new();
+
+    for line in &hunk.lines {
+        let text = match line {
+            Line::Context(text) => {
+                advance(&mut line_column, text);
+                expected += text;
+                if span.is_some() {
+                    pending += text;
+                }
+                continue;
+            }
+            Line::Removed(text) | Line::Added(text) => text,
+        };
+
+        let (_, end) = span.get_or_insert((line_column, line_column));
+        replacement += &pending;
+        pending.clear();
+        *end = line_column;
+
+        if let Line::Removed(_) = line {
+            advance(&mut line_column, text);
+            expected += text;
+            *end = line_column;
+        } else {
+            replacement += text;
+        }
+    }
+
+    let (start, end) = span.unwrap_or((line_column, line_column));
+
+    HunkEdit {
+        region: Span::new(region_start, line_column),
+        expected,
+        span: Span::new(start, end),
+        replacement,
+    }
+}
+
+#[allow(clippy::disallowed_methods)]
+fn advance(line_column: &mut LineColumn, text: &str) {
+    if text.ends_with('\n') {
+        line_column.line += 1;
+        line_column.column = 0;
+    } else {
+        line_column.column += text.chars().count();
+    }
+}
+
+fn parse_path(path: &str) -> String {
+    let path = path.trim_end_matches(['\n', '\r']);
+    path.split('\t').next().unwrap_or(path).to_owned()
+}
+
+fn parse_hunk_header(line: &str) -> Option<Hunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (original, rest) = rest.split_once(" +")?;
+    let (rewritten, _) = rest.split_once(" @@")?;
+    let (original_start, original_len) = parse_range(original)?;
+    let (rewritten_start, rewritten_len) = parse_range(rewritten)?;
+    Some(Hunk {
+        original_start,
+        original_len,
+        rewritten_start,
+        rewritten_len,
+        lines: Vec::new(),
+    })
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+fn strip_newline(hunk: &mut Hunk) {
+    if let Some(Line::Context(text) | Line::Removed(text) | Line::Added(text)) =
+        hunk.lines.last_mut()
+        && text.ends_with('\n')
+    {
+        text.pop();
+    }
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ContextMismatch { span } => write!(
+                f,
+                "context does not match original text at {:?}..{:?}",
+                crate::interface::Span::start(span),
+                crate::interface::Span::end(span),
+            ),
+            Self::Rewrite(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MalformedHunkHeader { line } => write!(f, "malformed hunk header at line {line}"),
+            Self::UnexpectedLine { line } => write!(f, "unexpected line {line}"),
+            Self::UnexpectedEof { line } => write!(f, "unexpected end of diff at line {line}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
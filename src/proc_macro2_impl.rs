@@ -1,4 +1,5 @@
-use crate::interface;
+use crate::{LineColumn, Span, interface};
+use std::ops::Range;
 
 impl interface::LineColumn for proc_macro2::LineColumn {
     fn line(&self) -> usize {
@@ -26,4 +27,64 @@ impl interface::Span for proc_macro2::Span {
     fn end(&self) -> Self::LineColumn {
         self.end()
     }
+    // smoelius: proc-macro2's fallback implementation records each span's byte range relative to
+    // the start of the text the span was parsed from.
+    fn byte_range(&self) -> Option<Range<usize>> {
+        Some(self.byte_range())
+    }
+    // smoelius: In proc-macro2's fallback implementation, two spans can be joined only if they come
+    // from the same file, and `Span::call_site()` belongs to no file.
+    fn has_location(&self) -> bool {
+        self.join(proc_macro2::Span::call_site()).is_none()
+    }
+    fn same_source(&self, other: &Self) -> bool {
+        self.join(*other).is_some()
+    }
+    // smoelius: A span from a different file is unlikely to have the same text as the part of
+    // `original` it resolves to. But if it does (e.g., the two files share a prefix), the span is
+    // accepted. The text is compared only for the first span a `Rewriter` sees.
+    fn belongs_to(&self, original: &str, range: Range<usize>) -> bool {
+        self.source_text()
+            .is_some_and(|source_text| original.get(range) == Some(source_text.as_str()))
+    }
+}
+
+impl From<proc_macro2::LineColumn> for LineColumn {
+    fn from(line_column: proc_macro2::LineColumn) -> Self {
+        Self {
+            line: line_column.line,
+            column: line_column.column,
+        }
+    }
+}
+
+impl From<LineColumn> for proc_macro2::LineColumn {
+    fn from(line_column: LineColumn) -> Self {
+        Self {
+            line: line_column.line,
+            column: line_column.column,
+        }
+    }
+}
+
+impl From<(proc_macro2::LineColumn, proc_macro2::LineColumn)> for Span {
+    fn from((start, end): (proc_macro2::LineColumn, proc_macro2::LineColumn)) -> Self {
+        Self::new(start.into(), end.into())
+    }
+}
+
+impl From<Span> for (proc_macro2::LineColumn, proc_macro2::LineColumn) {
+    fn from(span: Span) -> Self {
+        use interface::Span as _;
+
+        (span.start().into(), span.end().into())
+    }
+}
+
+// smoelius: A `proc_macro2::Span` cannot be constructed from line/column pairs, so there is no
+// conversion in the other direction.
+impl From<proc_macro2::Span> for Span {
+    fn from(span: proc_macro2::Span) -> Self {
+        (span.start(), span.end()).into()
+    }
 }
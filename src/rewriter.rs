@@ -1,21 +1,84 @@
-use crate::SpanDefault;
+use crate::diff::Diff;
 use crate::interface::Span;
 use crate::offset_based_rewriter::{self, OffsetBasedRewriter};
-use crate::offset_calculator::OffsetCalculator;
-use std::{cell::RefCell, rc::Rc};
+use crate::offset_calculator::{self, OffsetCalculator, SharedOffsetCalculator, SpanOffsets};
+use crate::source_map::{Edit, SourceMap};
+use crate::{
+    ByteSpan, ColumnEncoding, LineEnding, LineEndingPolicy, RewriteError, SpanDefault, TextFormat,
+};
+use std::{borrow::Cow, cell::RefCell, rc::Rc, sync::Arc};
 
 #[derive(Debug)]
 pub struct Rewriter<'original, S: Span = SpanDefault> {
+    original: &'original str,
+    offset_calculator: Calculator<'original, S>,
+    state: State<S>,
+}
+
+#[derive(Debug)]
+enum Calculator<'original, S: Span> {
+    Local(Rc<RefCell<OffsetCalculator<'original, S>>>),
+    Shared(Arc<SharedOffsetCalculator<S>>),
+}
+
+/// The state shared by [`Rewriter`] and [`OwnedRewriter`]
+///
+/// The original text and the offset calculator are passed to each method that needs them rather
+/// than stored.
+///
+/// [`OwnedRewriter`]: crate::OwnedRewriter
+#[derive(Clone, Debug)]
+pub(crate) struct State<S: Span> {
+    /// The end of the most recently rewritten line/column span
     line_column: S::LineColumn,
-    offset_calculator: Rc<RefCell<OffsetCalculator<'original, S>>>,
-    offset_based_rewriter: OffsetBasedRewriter<'original>,
+    /// The end of the most recently rewritten span of either kind, as a byte offset
+    offset: usize,
+    /// A span known to come from the original text, against which other spans are compared
+    source: Option<S>,
+    offset_based_rewriter: OffsetBasedRewriter,
+    edits: Vec<Edit>,
+    /// The original text's dominant line ending, used by `line_ending_policy`
+    line_ending: LineEnding,
+    line_ending_policy: LineEndingPolicy,
+    /// How the offset calculator counts lines and columns, used by line-based output such as
+    /// [`Diff`]s
+    format: TextFormat,
+}
+
+/// The state of a [`Rewriter`], to which it can be returned with [`Rewriter::rollback_to`]
+///
+/// A `Checkpoint` of an [`OwnedRewriter`] can likewise be passed to [`OwnedRewriter::rollback_to`].
+///
+/// [`OwnedRewriter`]: crate::OwnedRewriter
+/// [`OwnedRewriter::rollback_to`]: crate::OwnedRewriter::rollback_to
+#[derive(Clone, Debug)]
+pub struct Checkpoint<S: Span = SpanDefault> {
+    line_column: S::LineColumn,
+    offset: usize,
+    source: Option<S>,
+    offset_calculator: Option<offset_calculator::Checkpoint<S>>,
+    offset_based_rewriter: offset_based_rewriter::Checkpoint,
+    n_edits: usize,
 }
 
 impl<'original, S: Span> Rewriter<'original, S> {
     #[must_use]
     pub fn new(original: &'original str) -> Self {
-        let offset_calculator =
-            Rc::new(RefCell::new(OffsetCalculator::new_private(original, false)));
+        Self::with_encoding(original, S::COLUMN_ENCODING)
+    }
+
+    /// Like [`Rewriter::new`], but interprets spans' columns in `encoding`
+    #[must_use]
+    pub fn with_encoding(original: &'original str, encoding: ColumnEncoding) -> Self {
+        Self::with_format(original, encoding.into())
+    }
+
+    /// Like [`Rewriter::new`], but interprets spans' lines and columns according to `format`
+    #[must_use]
+    pub fn with_format(original: &'original str, format: TextFormat) -> Self {
+        let offset_calculator = Rc::new(RefCell::new(OffsetCalculator::new_private(
+            original, false, format,
+        )));
         Self::with_offset_calculator(original, offset_calculator)
     }
 
@@ -23,22 +86,380 @@ impl<'original, S: Span> Rewriter<'original, S> {
         original: &'original str,
         offset_calculator: Rc<RefCell<OffsetCalculator<'original, S>>>,
     ) -> Self {
+        let format = offset_calculator.borrow().state.format();
         Self {
-            line_column: S::line_column(1, 0),
+            original,
+            offset_calculator: Calculator::Local(offset_calculator),
+            state: State::new(original, format),
+        }
+    }
+
+    /// Constructs a `Rewriter` for the original text of `offset_calculator`
+    ///
+    /// Unlike an [`OffsetCalculator`], a [`SharedOffsetCalculator`] can be shared by `Rewriter`s on
+    /// different threads.
+    #[must_use]
+    pub fn with_shared_offset_calculator(
+        offset_calculator: &'original Arc<SharedOffsetCalculator<S>>,
+    ) -> Self {
+        let original = &**offset_calculator.original();
+        Self {
+            original,
+            offset_calculator: Calculator::Shared(offset_calculator.clone()),
+            state: State::new(original, offset_calculator.line_index().format()),
+        }
+    }
+
+    /// Returns a copy of the `Rewriter` that can be rewritten independently
+    ///
+    /// The copy starts with the rewrites performed so far. If the `Rewriter`'s [`OffsetCalculator`]
+    /// has a [`LineIndex`] (e.g., it was passed to [`Rewriter::with_offset_calculator`]) or is a
+    /// [`SharedOffsetCalculator`], the calculator is shared with the copy. Otherwise, the
+    /// calculator's current position is copied. Either way, the original text is not rescanned up
+    /// to the current position.
+    ///
+    /// [`LineIndex`]: crate::LineIndex
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        let offset_calculator = match &self.offset_calculator {
+            Calculator::Local(offset_calculator)
+                if !offset_calculator.borrow().state.has_line_index() =>
+            {
+                Calculator::Local(Rc::new(RefCell::new(offset_calculator.borrow().clone())))
+            }
+            Calculator::Local(offset_calculator) => Calculator::Local(offset_calculator.clone()),
+            Calculator::Shared(offset_calculator) => Calculator::Shared(offset_calculator.clone()),
+        };
+
+        Self {
+            original: self.original,
             offset_calculator,
-            offset_based_rewriter: OffsetBasedRewriter::new(original),
+            state: self.state.clone(),
         }
     }
 
+    /// Returns one variant of `original` per edit, each with just that edit applied
+    ///
+    /// The variants are returned in the same order as `edits`. A single [`OffsetCalculator`] is
+    /// shared by all of the variants, so `original` is scanned only once.
+    pub fn variants<'a>(
+        original: &'original str,
+        edits: impl IntoIterator<Item = (&'a S, &'a str)>,
+    ) -> Vec<String>
+    where
+        S: 'a,
+    {
+        let offset_calculator = Rc::new(RefCell::new(OffsetCalculator::new(original)));
+        let rewriter = Self::with_offset_calculator(original, offset_calculator);
+        edits
+            .into_iter()
+            .map(|(span, replacement)| {
+                let mut fork = rewriter.fork();
+                let _: String = fork.rewrite(span, replacement);
+                fork.contents()
+            })
+            .collect()
+    }
+
+    /// Sets how line breaks in subsequent replacements, and at the end of the contents, are
+    /// treated
+    ///
+    /// The original text's dominant [`LineEnding`] is detected when this method is called.
+    pub fn set_line_ending_policy(&mut self, policy: LineEndingPolicy) {
+        self.state.set_line_ending_policy(self.original, policy);
+    }
+
+    /// Sets a span known to come from the original text
+    ///
+    /// Subsequent spans that do not come from the same text (see [`Span::same_source`]) are
+    /// rejected. If no source span is set, the first span rewritten becomes the source span,
+    /// provided that it belongs to the original text (see [`Span::belongs_to`]).
+    ///
+    /// That check is only a heuristic. For example, a span from a different text that covers the
+    /// same text at the same position is accepted. Setting a source span avoids relying on it.
+    pub fn set_source_span(&mut self, span: S) {
+        self.state.set_source_span(span);
+    }
+
     pub fn contents(self) -> String {
-        use offset_based_rewriter::Interface;
+        self.state.contents(self.original)
+    }
 
-        self.offset_based_rewriter.contents()
+    /// Returns the contents as they would be if [`Rewriter::contents`] were called now, without
+    /// consuming the `Rewriter`
+    ///
+    /// This allows the intermediate text to be inspected (e.g., parsed) before further rewrites are
+    /// performed.
+    #[must_use]
+    pub fn preview(&self) -> String {
+        self.state.preview(self.original)
     }
 
+    /// Like [`Rewriter::contents`], but also returns a [`SourceMap`] relating the original and
+    /// rewritten texts
+    pub fn contents_with_source_map(self) -> (String, SourceMap<S>) {
+        self.state.contents_with_source_map(self.original)
+    }
+
+    /// Returns a [`Checkpoint`] of the `Rewriter`'s current state
+    ///
+    /// Passing the checkpoint to [`Rewriter::rollback_to`] undoes the rewrites performed since the
+    /// checkpoint was taken. This allows a group of related rewrites to be applied all-or-nothing.
+    #[must_use]
+    pub fn checkpoint(&self) -> Checkpoint<S> {
+        self.offset_calculator
+            .with(|offset_calculator| self.state.checkpoint(offset_calculator))
+    }
+
+    /// Undoes the rewrites performed since `checkpoint` was taken
+    ///
+    /// Subsequent rewrites may again start anywhere at or after the end of the last rewrite
+    /// preceding the checkpoint.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `checkpoint` was taken after the `Rewriter`'s current state, e.g., if the
+    /// `Rewriter` was already rolled back to an earlier checkpoint.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint<S>) {
+        self.offset_calculator.with(|offset_calculator| {
+            self.state.rollback_to(offset_calculator, checkpoint);
+        });
+    }
+
+    /// Returns a unified diff of the rewrites performed so far, with `context` lines of context
+    ///
+    /// `path_label` is used in the diff's `---` and `+++` lines, prefixed with `a/` and `b/`
+    /// respectively.
+    #[must_use]
+    pub fn diff(&self, path_label: &str, context: usize) -> Diff {
+        self.state.diff(self.original, path_label, context)
+    }
+
+    /// Replaces the text covered by `span` with `replacement`, and returns the replaced text
+    ///
+    /// # Panics
+    ///
+    /// Panics if `span` starts before the end of the previously rewritten span, if `span` has no
+    /// location (see [`Span::has_location`]), or if `span` does not come from the original text
+    /// (see [`Rewriter::set_source_span`]).
     pub fn rewrite(&mut self, span: &S, replacement: &str) -> String {
+        self.offset_calculator.with(|offset_calculator| {
+            self.state
+                .rewrite(self.original, offset_calculator, span, replacement)
+        })
+    }
+
+    /// Like [`Rewriter::rewrite`], but returns an error rather than panicking or clamping when
+    /// `span` is out of order, reversed, or not within the original text
+    ///
+    /// A span that does not come from the original text (see [`Rewriter::set_source_span`])
+    /// results in [`RewriteError::ForeignSpan`], and one with no location results in
+    /// [`RewriteError::NoLocation`]. When an error is returned, no rewrite is performed and the
+    /// `Rewriter` can continue to be used.
+    pub fn try_rewrite(
+        &mut self,
+        span: &S,
+        replacement: &str,
+    ) -> Result<String, RewriteError<S::LineColumn>> {
+        self.offset_calculator.with(|offset_calculator| {
+            self.state
+                .try_rewrite(self.original, offset_calculator, span, replacement)
+        })
+    }
+
+    /// Like [`Rewriter::rewrite`], but takes a span of byte offsets rather than of lines and
+    /// columns
+    ///
+    /// `span` is rewritten without consulting the `Rewriter`'s offset calculator. Byte spans and
+    /// line/column spans can be mixed in one `Rewriter`, but they must be in order with respect to
+    /// one another. An offset that falls within a character is rounded down to the start of that
+    /// character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `span` starts before the end of the previously rewritten span, or if `span` is
+    /// reversed.
+    pub fn rewrite_bytes(&mut self, span: impl Into<ByteSpan>, replacement: &str) -> String {
+        self.state
+            .rewrite_bytes(self.original, span.into(), replacement)
+    }
+
+    /// Like [`Rewriter::rewrite_bytes`], but returns an error rather than panicking or clamping
+    /// when `span` is out of order, reversed, or not within the original text
+    ///
+    /// The errors' "line-columns" are byte offsets.
+    pub fn try_rewrite_bytes(
+        &mut self,
+        span: impl Into<ByteSpan>,
+        replacement: &str,
+    ) -> Result<String, RewriteError<usize>> {
+        self.state
+            .try_rewrite_bytes(self.original, span.into(), replacement)
+    }
+}
+
+impl<S: Span> Calculator<'_, S> {
+    fn with<R>(&self, f: impl FnOnce(&mut dyn SpanOffsets<S>) -> R) -> R {
+        match self {
+            Self::Local(offset_calculator) => f(&mut offset_calculator.borrow_mut().state),
+            Self::Shared(offset_calculator) => f(&mut &**offset_calculator),
+        }
+    }
+}
+
+impl<S: Span> State<S> {
+    pub(crate) fn new(original: &str, format: TextFormat) -> Self {
+        Self {
+            line_column: S::line_column(1, 0),
+            offset: 0,
+            source: None,
+            offset_based_rewriter: OffsetBasedRewriter::new(original),
+            edits: Vec::new(),
+            line_ending: LineEnding::default(),
+            line_ending_policy: LineEndingPolicy::default(),
+            format,
+        }
+    }
+
+    pub(crate) fn set_line_ending_policy(&mut self, original: &str, policy: LineEndingPolicy) {
+        self.line_ending = LineEnding::detect(original).unwrap_or_default();
+        self.line_ending_policy = policy;
+    }
+
+    pub(crate) fn set_source_span(&mut self, span: S) {
+        self.source = Some(span);
+    }
+
+    pub(crate) fn contents(self, original: &str) -> String {
+        let (contents, _) = self.into_contents_and_edits(original);
+        contents
+    }
+
+    pub(crate) fn preview(&self, original: &str) -> String {
+        use offset_based_rewriter::Interface;
+
+        // smoelius: Fixing the trailing newline reads at most the last two edits, and the updated
+        // edits are discarded. So there is no need to copy the rest.
+        let mut edits = self.edits[self.edits.len().saturating_sub(2)..].to_vec();
+        let mut contents = self.offset_based_rewriter.preview(original);
+        self.line_ending_policy.trailing_newline.apply(
+            original,
+            self.line_ending,
+            self.format.line_terminators,
+            &mut contents,
+            &mut edits,
+        );
+        contents
+    }
+
+    pub(crate) fn contents_with_source_map(self, original: &str) -> (String, SourceMap<S>) {
+        let format = self.format;
+        let (contents, edits) = self.into_contents_and_edits(original);
+        let source_map = SourceMap::new(original, contents.clone(), edits, format);
+        (contents, source_map)
+    }
+
+    fn into_contents_and_edits(mut self, original: &str) -> (String, Vec<Edit>) {
         use offset_based_rewriter::Interface;
 
+        let mut contents = self.offset_based_rewriter.contents(original);
+        self.line_ending_policy.trailing_newline.apply(
+            original,
+            self.line_ending,
+            self.format.line_terminators,
+            &mut contents,
+            &mut self.edits,
+        );
+        (contents, self.edits)
+    }
+
+    pub(crate) fn checkpoint(
+        &self,
+        offset_calculator: &(impl SpanOffsets<S> + ?Sized),
+    ) -> Checkpoint<S> {
+        use offset_based_rewriter::Interface;
+
+        Checkpoint {
+            line_column: self.line_column,
+            offset: self.offset,
+            source: self.source.clone(),
+            offset_calculator: offset_calculator.checkpoint(),
+            offset_based_rewriter: self.offset_based_rewriter.checkpoint(),
+            n_edits: self.edits.len(),
+        }
+    }
+
+    pub(crate) fn rollback_to(
+        &mut self,
+        offset_calculator: &mut (impl SpanOffsets<S> + ?Sized),
+        checkpoint: Checkpoint<S>,
+    ) {
+        use offset_based_rewriter::Interface;
+
+        let Checkpoint {
+            line_column,
+            offset,
+            source,
+            offset_calculator: offset_calculator_checkpoint,
+            offset_based_rewriter,
+            n_edits,
+        } = checkpoint;
+
+        assert!(
+            line_column <= self.line_column && offset <= self.offset && n_edits <= self.edits.len(),
+            "checkpoint is ahead of the `Rewriter`: {:?} > {:?}",
+            line_column,
+            self.line_column,
+        );
+
+        self.line_column = line_column;
+        self.offset = offset;
+        self.source = source;
+        if let Some(offset_calculator_checkpoint) = offset_calculator_checkpoint {
+            offset_calculator.restore(&offset_calculator_checkpoint);
+        }
+        self.offset_based_rewriter
+            .rollback_to(offset_based_rewriter);
+        self.edits.truncate(n_edits);
+    }
+
+    pub(crate) fn diff(&self, original: &str, path_label: &str, context: usize) -> Diff {
+        use offset_based_rewriter::Interface;
+
+        let mut edits = self.edits.clone();
+        let mut rewritten = self.offset_based_rewriter.preview(original);
+        self.line_ending_policy.trailing_newline.apply(
+            original,
+            self.line_ending,
+            self.format.line_terminators,
+            &mut rewritten,
+            &mut edits,
+        );
+        Diff::new(
+            path_label,
+            original,
+            &rewritten,
+            &edits,
+            context,
+            self.format,
+        )
+    }
+
+    pub(crate) fn rewrite(
+        &mut self,
+        original: &str,
+        offset_calculator: &mut (impl SpanOffsets<S> + ?Sized),
+        span: &S,
+        replacement: &str,
+    ) -> String {
+        // smoelius: A span's location and source are checked before its order, since a span with
+        // no location or from a different text has no meaningful order.
+        assert!(span.has_location(), "span has no location: {span:?}");
+        assert!(
+            self.is_from_source(span),
+            "span does not belong to the original text: {span:?}"
+        );
+
         assert!(
             self.line_column <= span.start(),
             "self = {:#?}, span.start() = {:?}, span.end() = {:?}",
@@ -47,12 +468,192 @@ impl<'original, S: Span> Rewriter<'original, S> {
             span.end(),
         );
 
-        let (start, end) = self.offset_calculator.borrow_mut().offsets_from_span(span);
+        let (start, end) = offset_calculator.offsets_from_span(original, span);
+
+        assert!(
+            self.source.is_some() || span.belongs_to(original, start..end),
+            "span does not belong to the original text: {span:?}"
+        );
+
+        assert!(
+            self.offset <= start,
+            "self.offset = {}, span.start() = {:?}, start = {start}",
+            self.offset,
+            span.start(),
+        );
+
+        self.source.get_or_insert_with(|| span.clone());
+        self.line_column = span.end();
+
+        self.rewrite_offsets(original, start, end, replacement)
+    }
+
+    pub(crate) fn try_rewrite(
+        &mut self,
+        original: &str,
+        offset_calculator: &mut (impl SpanOffsets<S> + ?Sized),
+        span: &S,
+        replacement: &str,
+    ) -> Result<String, RewriteError<S::LineColumn>> {
+        // smoelius: See the comment in `State::rewrite` regarding the order of the checks.
+        if !span.has_location() {
+            return Err(RewriteError::NoLocation);
+        }
+
+        if !self.is_from_source(span) {
+            return Err(RewriteError::ForeignSpan {
+                start: span.start(),
+                end: span.end(),
+            });
+        }
+
+        if span.start() < self.line_column {
+            return Err(RewriteError::OutOfOrder {
+                previous_end: self.line_column,
+                start: span.start(),
+            });
+        }
+
+        let checkpoint = offset_calculator.checkpoint();
+
+        let (start, end) = offset_calculator.try_offsets_from_span(original, span)?;
+
+        if self.source.is_none() && !span.belongs_to(original, start..end) {
+            if let Some(checkpoint) = checkpoint {
+                offset_calculator.restore(&checkpoint);
+            }
+            return Err(RewriteError::ForeignSpan {
+                start: span.start(),
+                end: span.end(),
+            });
+        }
 
-        let replaced = self.offset_based_rewriter.rewrite(start, end, replacement);
+        // smoelius: The span can follow the previous line/column span yet precede a subsequent byte
+        // span.
+        if start < self.offset {
+            if let Some(checkpoint) = checkpoint {
+                offset_calculator.restore(&checkpoint);
+            }
+            return Err(RewriteError::OutOfOrder {
+                previous_end: offset_calculator
+                    .line_column_from_offset(original, self.offset.min(original.len())),
+                start: span.start(),
+            });
+        }
 
+        self.source.get_or_insert_with(|| span.clone());
         self.line_column = span.end();
 
+        Ok(self.rewrite_offsets(original, start, end, replacement))
+    }
+
+    pub(crate) fn rewrite_bytes(
+        &mut self,
+        original: &str,
+        span: ByteSpan,
+        replacement: &str,
+    ) -> String {
+        assert!(
+            self.offset <= span.start && span.start <= span.end,
+            "self.offset = {}, span = {span:?}",
+            self.offset,
+        );
+
+        if original.len() < span.start {
+            self.offset = span.end;
+            return String::new();
+        }
+
+        let start = original.floor_char_boundary(span.start);
+        let end = original.floor_char_boundary(span.end);
+
+        self.rewrite_offsets(original, start, end, replacement)
+    }
+
+    pub(crate) fn try_rewrite_bytes(
+        &mut self,
+        original: &str,
+        span: ByteSpan,
+        replacement: &str,
+    ) -> Result<String, RewriteError<usize>> {
+        if span.start < self.offset {
+            return Err(RewriteError::OutOfOrder {
+                previous_end: self.offset,
+                start: span.start,
+            });
+        }
+
+        if span.end < span.start {
+            return Err(RewriteError::ReversedSpan {
+                start: span.start,
+                end: span.end,
+            });
+        }
+
+        if original.len() < span.end {
+            return Err(RewriteError::PastEndOfFile {
+                line_column: span.end,
+                end_of_file: original.len(),
+            });
+        }
+
+        Ok(self.rewrite_bytes(original, span, replacement))
+    }
+
+    /// Returns whether `span` comes from the same text as the source span, if one is known
+    ///
+    /// Without a source span, the first span rewritten becomes the source span, provided that it
+    /// belongs to the original text (see [`Span::belongs_to`]).
+    fn is_from_source(&self, span: &S) -> bool {
+        self.source
+            .as_ref()
+            .is_none_or(|source| source.same_source(span))
+    }
+
+    fn rewrite_offsets(
+        &mut self,
+        original: &str,
+        start: usize,
+        end: usize,
+        replacement: &str,
+    ) -> String {
+        use offset_based_rewriter::Interface;
+
+        let replacement = self.normalize(replacement);
+
+        let replaced = self
+            .offset_based_rewriter
+            .rewrite(original, start, end, &replacement);
+
+        self.record_edit(original, start, &replaced, &replacement);
+
+        self.offset = end;
+
         replaced
     }
+
+    fn normalize<'a>(&self, replacement: &'a str) -> Cow<'a, str> {
+        if self.line_ending_policy.normalize_replacements {
+            self.line_ending.normalize(replacement)
+        } else {
+            Cow::Borrowed(replacement)
+        }
+    }
+
+    fn record_edit(&mut self, original: &str, start: usize, replaced: &str, replacement: &str) {
+        // smoelius: Out-of-bounds spans are not rewritten. See `LazyRewriter::rewrite`.
+        if original.len() < start {
+            return;
+        }
+
+        let rewritten_start = self
+            .edits
+            .last()
+            .map_or(start, |edit| start - edit.original.end + edit.rewritten.end);
+
+        self.edits.push(Edit {
+            original: start..start + replaced.len(),
+            rewritten: rewritten_start..rewritten_start + replacement.len(),
+        });
+    }
 }
@@ -0,0 +1,163 @@
+use crate::interface::{LineColumn, Span};
+use crate::{LineIndex, SpanDefault, TextFormat};
+use std::{marker::PhantomData, ops::Range};
+
+/// A rewrite, as recorded by a [`Rewriter`]
+///
+/// Both ranges are byte offsets. `original` is the range of the replaced text in the original, and
+/// `rewritten` is the range of the replacement in the rewritten text.
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Edit {
+    pub original: Range<usize>,
+    pub rewritten: Range<usize>,
+}
+
+/// Converts positions in a [`Rewriter`]'s original text to positions in its rewritten text, and
+/// vice versa
+///
+/// A position within text that was not replaced maps to the corresponding position on the other
+/// side. The start of a replaced range maps to the start of its replacement (and vice versa). A
+/// position strictly inside a replaced range or a replacement has no counterpart, and maps to
+/// `None`.
+///
+/// Lines and columns are counted according to the `Rewriter`'s [`TextFormat`].
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Debug)]
+pub struct SourceMap<S: Span = SpanDefault> {
+    original: Text,
+    rewritten: Text,
+    edits: Vec<Edit>,
+    phantom: PhantomData<S>,
+}
+
+#[derive(Debug)]
+struct Text {
+    text: String,
+    line_index: LineIndex,
+}
+
+impl<S: Span> SourceMap<S> {
+    pub(crate) fn new(
+        original: &str,
+        rewritten: String,
+        edits: Vec<Edit>,
+        format: TextFormat,
+    ) -> Self {
+        Self {
+            original: Text::new(original.to_owned(), format),
+            rewritten: Text::new(rewritten, format),
+            edits,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the recorded edits, in the order they were performed
+    #[must_use]
+    pub fn edits(&self) -> &[Edit] {
+        &self.edits
+    }
+
+    /// Returns the start and end of each replacement in the rewritten text, in the order the edits
+    /// were performed
+    pub fn replacements(&self) -> impl Iterator<Item = Range<S::LineColumn>> + '_ {
+        self.edits.iter().map(|edit| {
+            let start = self.rewritten.line_column::<S>(edit.rewritten.start);
+            let end = self.rewritten.line_column::<S>(edit.rewritten.end);
+            start..end
+        })
+    }
+
+    /// Maps an offset in the original text to an offset in the rewritten text
+    #[must_use]
+    pub fn rewritten_offset(&self, offset: usize) -> Option<usize> {
+        if self.original.text.len() < offset {
+            return None;
+        }
+        map_offset(
+            &self.edits,
+            offset,
+            |edit| &edit.original,
+            |edit| &edit.rewritten,
+        )
+    }
+
+    /// Maps an offset in the rewritten text to an offset in the original text
+    #[must_use]
+    pub fn original_offset(&self, offset: usize) -> Option<usize> {
+        if self.rewritten.text.len() < offset {
+            return None;
+        }
+        map_offset(
+            &self.edits,
+            offset,
+            |edit| &edit.rewritten,
+            |edit| &edit.original,
+        )
+    }
+
+    /// Maps a `LineColumn` in the original text to a `LineColumn` in the rewritten text
+    #[must_use]
+    pub fn rewritten_line_column(&self, line_column: S::LineColumn) -> Option<S::LineColumn> {
+        let offset = self.original.offset(line_column)?;
+        let offset = self.rewritten_offset(offset)?;
+        Some(self.rewritten.line_column::<S>(offset))
+    }
+
+    /// Maps a `LineColumn` in the rewritten text to a `LineColumn` in the original text
+    #[must_use]
+    pub fn original_line_column(&self, line_column: S::LineColumn) -> Option<S::LineColumn> {
+        let offset = self.rewritten.offset(line_column)?;
+        let offset = self.original_offset(offset)?;
+        Some(self.original.line_column::<S>(offset))
+    }
+}
+
+fn map_offset(
+    edits: &[Edit],
+    offset: usize,
+    from: impl Fn(&Edit) -> &Range<usize>,
+    to: impl Fn(&Edit) -> &Range<usize>,
+) -> Option<usize> {
+    // smoelius: `edits` are sorted and non-overlapping on both sides. `index` is the index of the
+    // first edit that does not start before `offset`.
+    let index = edits.partition_point(|edit| from(edit).start < offset);
+
+    if let Some(edit) = edits.get(index)
+        && from(edit).start == offset
+    {
+        return Some(to(edit).start);
+    }
+
+    let Some(index_prev) = index.checked_sub(1) else {
+        return Some(offset);
+    };
+
+    let edit = &edits[index_prev];
+
+    if offset < from(edit).end {
+        return None;
+    }
+
+    Some(offset - from(edit).end + to(edit).end)
+}
+
+impl Text {
+    fn new(text: String, format: TextFormat) -> Self {
+        let line_index = LineIndex::with_format(&text, format);
+        Self { text, line_index }
+    }
+
+    fn offset<L: LineColumn>(&self, line_column: L) -> Option<usize> {
+        self.line_index
+            .offset_from_line_column(&self.text, line_column)
+            .ok()
+    }
+
+    fn line_column<S: Span>(&self, offset: usize) -> S::LineColumn {
+        let (line, column) = self.line_index.line_and_column(&self.text, offset).unwrap();
+        S::line_column(line, column)
+    }
+}
@@ -1,5 +1,5 @@
 use crate::interface;
-use std::cmp::Ordering;
+use std::{cmp::Ordering, ops::Range};
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct LineColumn {
@@ -50,7 +50,7 @@ impl interface::LineColumn for LineColumn {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub struct Span {
     start: LineColumn,
     end: LineColumn,
@@ -78,3 +78,52 @@ impl interface::Span for Span {
         self.end
     }
 }
+
+impl interface::FromLineColumns for Span {
+    fn from_line_columns(start: LineColumn, end: LineColumn) -> Self {
+        Self::new(start, end)
+    }
+}
+
+impl From<Range<LineColumn>> for Span {
+    fn from(range: Range<LineColumn>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<Span> for Range<LineColumn> {
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+
+/// A span given by byte offsets into the original text rather than by lines and columns
+///
+/// A `ByteSpan` can be passed to [`Rewriter::rewrite_bytes`], which does not consult the
+/// `Rewriter`'s offset calculator. A `Range<usize>` can be passed wherever a `ByteSpan` can.
+///
+/// [`Rewriter::rewrite_bytes`]: crate::Rewriter::rewrite_bytes
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct ByteSpan {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl ByteSpan {
+    #[must_use]
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+impl From<Range<usize>> for ByteSpan {
+    fn from(range: Range<usize>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<ByteSpan> for Range<usize> {
+    fn from(span: ByteSpan) -> Self {
+        span.start..span.end
+    }
+}
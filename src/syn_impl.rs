@@ -0,0 +1,73 @@
+use crate::{OwnedRewriter, RewriteError, Rewriter};
+use proc_macro2::{LineColumn, Span};
+use quote::ToTokens;
+use syn::spanned::Spanned;
+
+/// Helpers for rewriting `syn` nodes, e.g., `syn::Expr`s and `syn::Item`s
+///
+/// A node is located by its [`Spanned::span`], which joins the spans of the node's first and last
+/// tokens. A node with no tokens has no location and so cannot be rewritten.
+///
+/// If the first and last tokens' spans cannot be joined (e.g., because the node was assembled from
+/// tokens of different files), [`Spanned::span`] returns just the first token's span.
+impl Rewriter<'_, Span> {
+    /// Replaces `node` with `replacement`, and returns the replaced text
+    ///
+    /// # Panics
+    ///
+    /// Panics if [`Rewriter::rewrite`] would panic for `node`'s span.
+    pub fn rewrite_node(&mut self, node: &impl Spanned, replacement: &str) -> String {
+        self.rewrite(&node.span(), replacement)
+    }
+
+    /// Like [`Rewriter::rewrite_node`], but returns an error rather than panicking
+    ///
+    /// A node with no tokens results in [`RewriteError::NoLocation`].
+    pub fn try_rewrite_node(
+        &mut self,
+        node: &impl Spanned,
+        replacement: &str,
+    ) -> Result<String, RewriteError<LineColumn>> {
+        self.try_rewrite(&node.span(), replacement)
+    }
+
+    /// Removes `node`, and returns the removed text
+    ///
+    /// Surrounding whitespace and punctuation (e.g., a trailing comma) are not removed.
+    pub fn delete_node(&mut self, node: &impl Spanned) -> String {
+        self.rewrite_node(node, "")
+    }
+
+    /// Replaces `node` with the text of `tokens`, and returns the replaced text
+    ///
+    /// `tokens` are converted to text with [`ToString`], so the replacement is not formatted.
+    pub fn replace_with_tokens(&mut self, node: &impl Spanned, tokens: &impl ToTokens) -> String {
+        self.rewrite_node(node, &tokens.to_token_stream().to_string())
+    }
+}
+
+impl OwnedRewriter<Span> {
+    /// See [`Rewriter::rewrite_node`].
+    pub fn rewrite_node(&mut self, node: &impl Spanned, replacement: &str) -> String {
+        self.rewrite(&node.span(), replacement)
+    }
+
+    /// See [`Rewriter::try_rewrite_node`].
+    pub fn try_rewrite_node(
+        &mut self,
+        node: &impl Spanned,
+        replacement: &str,
+    ) -> Result<String, RewriteError<LineColumn>> {
+        self.try_rewrite(&node.span(), replacement)
+    }
+
+    /// See [`Rewriter::delete_node`].
+    pub fn delete_node(&mut self, node: &impl Spanned) -> String {
+        self.rewrite_node(node, "")
+    }
+
+    /// See [`Rewriter::replace_with_tokens`].
+    pub fn replace_with_tokens(&mut self, node: &impl Spanned, tokens: &impl ToTokens) -> String {
+        self.rewrite_node(node, &tokens.to_token_stream().to_string())
+    }
+}
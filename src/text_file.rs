@@ -0,0 +1,184 @@
+use std::{
+    io::{Error, ErrorKind, Result},
+    path::Path,
+};
+
+const UTF_8_BOM: &[u8] = b"\xEF\xBB\xBF";
+const UTF_16_LE_BOM: &[u8] = b"\xFF\xFE";
+const UTF_16_BE_BOM: &[u8] = b"\xFE\xFF";
+
+/// The character encoding of a file
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum FileEncoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// ISO-8859-1, in which each byte is the Unicode scalar value of the same number
+    Latin1,
+}
+
+/// A file's contents decoded to a `String`, along with what is needed to encode them again
+///
+/// The contents do not include any byte order mark (BOM). So the columns of a [`Rewriter`]
+/// constructed from [`TextFile::contents`] are correct on the file's first line. When the
+/// rewritten contents are encoded, they are encoded in the file's original encoding, and the BOM is
+/// restored if the file had one.
+///
+/// [`Rewriter`]: crate::Rewriter
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TextFile {
+    contents: String,
+    encoding: FileEncoding,
+    bom: bool,
+}
+
+impl TextFile {
+    /// Reads and decodes the file at `path`
+    ///
+    /// See [`TextFile::decode`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::decode(&bytes)
+    }
+
+    /// Reads the file at `path` and decodes it as `encoding`
+    ///
+    /// See [`TextFile::decode_with_encoding`].
+    pub fn read_with_encoding(path: impl AsRef<Path>, encoding: FileEncoding) -> Result<Self> {
+        let bytes = std::fs::read(path)?;
+        Self::decode_with_encoding(&bytes, encoding)
+    }
+
+    /// Decodes `bytes`, detecting their encoding from their BOM
+    ///
+    /// Bytes without a BOM are decoded as UTF-8. Latin-1, and UTF-16 without a BOM, must be
+    /// requested explicitly with [`TextFile::decode_with_encoding`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorKind::InvalidData`] if `bytes` are not valid in the detected encoding.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let encoding = if bytes.starts_with(UTF_16_LE_BOM) {
+            FileEncoding::Utf16Le
+        } else if bytes.starts_with(UTF_16_BE_BOM) {
+            FileEncoding::Utf16Be
+        } else {
+            FileEncoding::Utf8
+        };
+        Self::decode_with_encoding(bytes, encoding)
+    }
+
+    /// Decodes `bytes` as `encoding`
+    ///
+    /// A leading BOM for `encoding` is removed and remembered.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorKind::InvalidData`] if `bytes` are not valid in `encoding`.
+    pub fn decode_with_encoding(bytes: &[u8], encoding: FileEncoding) -> Result<Self> {
+        let (bytes, bom) = match encoding.bom().and_then(|bom| bytes.strip_prefix(bom)) {
+            Some(bytes) => (bytes, true),
+            None => (bytes, false),
+        };
+
+        let contents = match encoding {
+            FileEncoding::Utf8 => std::str::from_utf8(bytes)
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error))?
+                .to_owned(),
+            FileEncoding::Utf16Le => decode_utf16(bytes, u16::from_le_bytes)?,
+            FileEncoding::Utf16Be => decode_utf16(bytes, u16::from_be_bytes)?,
+            FileEncoding::Latin1 => bytes.iter().copied().map(char::from).collect(),
+        };
+
+        Ok(Self {
+            contents,
+            encoding,
+            bom,
+        })
+    }
+
+    /// Returns the decoded contents, without any BOM
+    #[must_use]
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+
+    #[must_use]
+    pub fn encoding(&self) -> FileEncoding {
+        self.encoding
+    }
+
+    /// Returns whether the file had a BOM
+    #[must_use]
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Encodes `contents` in the file's encoding, preceded by the file's BOM if it had one
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ErrorKind::InvalidData`] if `contents` contains a character that cannot be
+    /// represented in the file's encoding (i.e., a character past U+00FF in a Latin-1 file).
+    #[allow(clippy::disallowed_methods)]
+    pub fn encode(&self, contents: &str) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        if self.bom {
+            bytes.extend_from_slice(self.encoding.bom().unwrap_or_default());
+        }
+
+        match self.encoding {
+            FileEncoding::Utf8 => bytes.extend_from_slice(contents.as_bytes()),
+            FileEncoding::Utf16Le => {
+                bytes.extend(contents.encode_utf16().flat_map(u16::to_le_bytes));
+            }
+            FileEncoding::Utf16Be => {
+                bytes.extend(contents.encode_utf16().flat_map(u16::to_be_bytes));
+            }
+            FileEncoding::Latin1 => {
+                for c in contents.chars() {
+                    let byte = u8::try_from(c).map_err(|_| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("{c:?} cannot be encoded as Latin-1"),
+                        )
+                    })?;
+                    bytes.push(byte);
+                }
+            }
+        }
+
+        Ok(bytes)
+    }
+
+    /// Encodes `contents` as with [`TextFile::encode`] and writes them to `path`
+    pub fn write(&self, path: impl AsRef<Path>, contents: &str) -> Result<()> {
+        let bytes = self.encode(contents)?;
+        std::fs::write(path, bytes)
+    }
+}
+
+impl FileEncoding {
+    fn bom(self) -> Option<&'static [u8]> {
+        match self {
+            Self::Utf8 => Some(UTF_8_BOM),
+            Self::Utf16Le => Some(UTF_16_LE_BOM),
+            Self::Utf16Be => Some(UTF_16_BE_BOM),
+            Self::Latin1 => None,
+        }
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String> {
+    let (chunks, remainder) = bytes.as_chunks::<2>();
+    if !remainder.is_empty() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "UTF-16 data has an odd number of bytes",
+        ));
+    }
+    char::decode_utf16(chunks.iter().copied().map(from_bytes))
+        .collect::<std::result::Result<String, _>>()
+        .map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
@@ -0,0 +1,98 @@
+use crate::ColumnEncoding;
+
+/// How a text's lines and columns are counted
+///
+/// The default is to end lines only at `\n` and to count columns in characters, as `proc_macro2`
+/// does.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TextFormat {
+    pub column_encoding: ColumnEncoding,
+    pub line_terminators: LineTerminators,
+}
+
+/// The character sequences that end a line
+///
+/// A line's terminator is not part of the line. So a span that ends at the end of a line does not
+/// include the terminator, regardless of its length.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineTerminators {
+    /// `\n` only, as in `proc_macro2` and `str::split('\n')`
+    ///
+    /// In a CRLF file, the `\r` is the last character of each line.
+    #[default]
+    Lf,
+    /// `\n` and `\r\n`, as in [`str::lines`]
+    LfCrlf,
+    /// `\n`, `\r\n`, and a lone `\r`, as in the Language Server Protocol
+    CrLfCrlf,
+    /// Those of [`LineTerminators::CrLfCrlf`], plus U+2028 LINE SEPARATOR and U+2029 PARAGRAPH
+    /// SEPARATOR
+    Unicode,
+}
+
+impl From<ColumnEncoding> for TextFormat {
+    fn from(column_encoding: ColumnEncoding) -> Self {
+        Self {
+            column_encoding,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<LineTerminators> for TextFormat {
+    fn from(line_terminators: LineTerminators) -> Self {
+        Self {
+            line_terminators,
+            ..Self::default()
+        }
+    }
+}
+
+impl LineTerminators {
+    /// Returns the offset and length of the first line terminator in `text`, or `None` if there is
+    /// none
+    #[allow(clippy::disallowed_methods)]
+    pub(crate) fn find(self, text: &str) -> Option<(usize, usize)> {
+        let index = match self {
+            Self::Lf => return text.find('\n').map(|index| (index, 1)),
+            Self::LfCrlf => {
+                let index = text.find('\n')?;
+                return Some(if text[..index].ends_with('\r') {
+                    (index - 1, 2)
+                } else {
+                    (index, 1)
+                });
+            }
+            Self::CrLfCrlf => text.find(['\r', '\n'])?,
+            Self::Unicode => text.find(['\r', '\n', '\u{2028}', '\u{2029}'])?,
+        };
+        let suffix = &text[index..];
+        let len = if suffix.starts_with("\r\n") {
+            2
+        } else {
+            suffix.chars().next().unwrap().len_utf8()
+        };
+        Some((index, len))
+    }
+
+    /// Returns the length of the line terminator at the end of `text`, or 0 if there is none
+    ///
+    /// A `\r\n` is one terminator even for [`LineTerminators::Lf`], so that removing a trailing
+    /// terminator never leaves a lone `\r`.
+    #[allow(clippy::disallowed_methods)]
+    pub(crate) fn trailing_len(self, text: &str) -> usize {
+        if text.ends_with("\r\n") {
+            return 2;
+        }
+        let Some(c) = text.chars().next_back() else {
+            return 0;
+        };
+        let terminator = match c {
+            '\n' => true,
+            '\r' => matches!(self, Self::CrLfCrlf | Self::Unicode),
+            '\u{2028}' | '\u{2029}' => self == Self::Unicode,
+            _ => false,
+        };
+        if terminator { c.len_utf8() } else { 0 }
+    }
+}
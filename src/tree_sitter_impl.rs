@@ -0,0 +1,45 @@
+use crate::{ColumnEncoding, interface};
+use std::ops::Range;
+
+// smoelius: A `tree_sitter::Point`'s row is 0-based, whereas `interface::LineColumn::line` is
+// 1-based. So `tree_sitter::Range`'s `start` and `end` add one to the row, and the `Point`s the
+// rewriter works with (e.g., those returned by `OffsetCalculator::line_column_from_offset`) have
+// 1-based rows. This way, `line` and `line_mut` refer to the same value.
+impl interface::LineColumn for tree_sitter::Point {
+    fn line(&self) -> usize {
+        self.row
+    }
+    fn line_mut(&mut self) -> &mut usize {
+        &mut self.row
+    }
+    fn column(&self) -> usize {
+        self.column
+    }
+    fn column_mut(&mut self) -> &mut usize {
+        &mut self.column
+    }
+}
+
+impl interface::Span for tree_sitter::Range {
+    type LineColumn = tree_sitter::Point;
+    const COLUMN_ENCODING: ColumnEncoding = ColumnEncoding::Bytes;
+    fn line_column(line: usize, column: usize) -> Self::LineColumn {
+        tree_sitter::Point { row: line, column }
+    }
+    fn start(&self) -> Self::LineColumn {
+        one_based(self.start_point)
+    }
+    fn end(&self) -> Self::LineColumn {
+        one_based(self.end_point)
+    }
+    fn byte_range(&self) -> Option<Range<usize>> {
+        Some(self.start_byte..self.end_byte)
+    }
+}
+
+fn one_based(point: tree_sitter::Point) -> tree_sitter::Point {
+    tree_sitter::Point {
+        row: point.row + 1,
+        column: point.column,
+    }
+}
@@ -0,0 +1,56 @@
+use rewriter::{ByteRewriter, RewriteError};
+
+#[test]
+fn byte_rewriter() {
+    let original = b"ab\xFFcd\xE9";
+    let mut rewriter = ByteRewriter::new(original);
+    assert_eq!(b"\xFF", rewriter.rewrite(2..3, b"x").as_slice());
+    assert_eq!(b"", rewriter.rewrite(4..4, b"\xFE\xFE").as_slice());
+    assert_eq!(b"abxc\xFE\xFEd\xE9", rewriter.preview().as_slice());
+    assert_eq!(b"\xE9", rewriter.rewrite(5..6, b"").as_slice());
+    assert_eq!(b"abxc\xFE\xFEd", rewriter.contents().as_slice());
+}
+
+#[test]
+fn clamp() {
+    let mut rewriter = ByteRewriter::new(b"abc");
+    assert_eq!(b"c", rewriter.rewrite(2..5, b"x").as_slice());
+    assert_eq!(b"", rewriter.rewrite(6..7, b"y").as_slice());
+    assert_eq!(b"abx", rewriter.contents().as_slice());
+}
+
+#[test]
+#[should_panic = "self.offset = 2, range = 0..1"]
+fn out_of_order() {
+    let mut rewriter = ByteRewriter::new(b"abc");
+    let _: Vec<u8> = rewriter.rewrite(1..2, b"x");
+    let _: Vec<u8> = rewriter.rewrite(0..1, b"y");
+}
+
+#[test]
+fn try_rewrite() {
+    let mut rewriter = ByteRewriter::new(b"abc");
+    assert_eq!(Ok(b"b".to_vec()), rewriter.try_rewrite(1..2, b"x"));
+    assert_eq!(
+        Err(RewriteError::OutOfOrder {
+            previous_end: 2,
+            start: 0
+        }),
+        rewriter.try_rewrite(0..1, b"y")
+    );
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 3..2;
+    assert_eq!(
+        Err(RewriteError::ReversedSpan { start: 3, end: 2 }),
+        rewriter.try_rewrite(reversed, b"y")
+    );
+    assert_eq!(
+        Err(RewriteError::PastEndOfFile {
+            line_column: 4,
+            end_of_file: 3
+        }),
+        rewriter.try_rewrite(2..4, b"y")
+    );
+    assert_eq!(Ok(b"c".to_vec()), rewriter.try_rewrite(2..3, b"y"));
+    assert_eq!(b"axy", rewriter.contents().as_slice());
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use common::{line_column, span};
+use rewriter::{ByteSpan, OwnedRewriter, RewriteError, Rewriter, Span};
+
+#[test]
+fn byte_span() {
+    let mut rewriter = Rewriter::<Span>::new("αβγ\nδε\n");
+    assert_eq!("β", rewriter.rewrite_bytes(2..4, "b"));
+    assert_eq!("γ", rewriter.rewrite(&span((1, 2), (1, 3)), "c"));
+    assert_eq!("δ", rewriter.rewrite_bytes(ByteSpan::new(7, 9), "d"));
+    assert_eq!("ε", rewriter.rewrite(&span((2, 1), (2, 2)), "e"));
+    assert_eq!("αbc\nde\n", rewriter.contents());
+}
+
+#[test]
+fn round_down() {
+    let mut rewriter = Rewriter::<Span>::new("αβ");
+    assert_eq!("α", rewriter.rewrite_bytes(1..3, "a"));
+    assert_eq!("aβ", rewriter.contents());
+}
+
+#[test]
+fn clamp() {
+    let mut rewriter = Rewriter::<Span>::new("abc");
+    assert_eq!("c", rewriter.rewrite_bytes(2..5, "x"));
+    assert_eq!("", rewriter.rewrite_bytes(6..7, "y"));
+    assert_eq!("abx", rewriter.contents());
+}
+
+#[test]
+#[should_panic = "self.offset = 2, span = ByteSpan { start: 0, end: 1 }"]
+fn out_of_order() {
+    let mut rewriter = Rewriter::<Span>::new("abc");
+    let _: String = rewriter.rewrite_bytes(1..2, "x");
+    let _: String = rewriter.rewrite_bytes(0..1, "y");
+}
+
+#[test]
+#[should_panic = "self.offset = 3"]
+fn line_column_span_before_byte_span() {
+    let mut rewriter = Rewriter::<Span>::new("abc");
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x");
+    let _: String = rewriter.rewrite_bytes(2..3, "y");
+    let _: String = rewriter.rewrite(&span((1, 1), (1, 2)), "z");
+}
+
+#[test]
+fn try_rewrite_bytes() {
+    let mut rewriter = Rewriter::<Span>::new("abc\ndef");
+    assert_eq!(Ok(String::from("b")), rewriter.try_rewrite_bytes(1..2, "x"));
+    assert_eq!(
+        Err(RewriteError::OutOfOrder {
+            previous_end: 2,
+            start: 0
+        }),
+        rewriter.try_rewrite_bytes(0..1, "y")
+    );
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 3..2;
+    assert_eq!(
+        Err(RewriteError::ReversedSpan { start: 3, end: 2 }),
+        rewriter.try_rewrite_bytes(reversed, "y")
+    );
+    assert_eq!(
+        Err(RewriteError::PastEndOfFile {
+            line_column: 8,
+            end_of_file: 7
+        }),
+        rewriter.try_rewrite_bytes(6..8, "y")
+    );
+    assert_eq!(Ok(String::from("d")), rewriter.try_rewrite_bytes(4..5, "y"));
+    assert_eq!(
+        Err(RewriteError::OutOfOrder {
+            previous_end: line_column(2, 1),
+            start: line_column(1, 2)
+        }),
+        rewriter.try_rewrite(&span((1, 2), (1, 3)), "z")
+    );
+    assert_eq!(
+        Ok(String::from("f")),
+        rewriter.try_rewrite(&span((2, 2), (2, 3)), "z")
+    );
+    assert_eq!("axc\nyez", rewriter.contents());
+}
+
+#[test]
+fn rollback() {
+    let mut rewriter = Rewriter::<Span>::new("abc");
+    let checkpoint = rewriter.checkpoint();
+    let _: String = rewriter.rewrite_bytes(1..2, "x");
+    rewriter.rollback_to(checkpoint);
+    assert_eq!("a", rewriter.rewrite_bytes(0..1, "y"));
+    assert_eq!("ybc", rewriter.contents());
+}
+
+#[test]
+fn owned_rewriter() {
+    let mut rewriter = OwnedRewriter::<Span>::new("abc");
+    assert_eq!("a", rewriter.rewrite(&span((1, 0), (1, 1)), "x"));
+    assert_eq!("b", rewriter.rewrite_bytes(1..2, "y"));
+    assert_eq!("xyc", rewriter.contents());
+}
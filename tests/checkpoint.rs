@@ -0,0 +1,39 @@
+mod common;
+
+use common::span;
+use rewriter::Rewriter;
+
+#[test]
+fn rollback() {
+    let mut rewriter = Rewriter::new("a\nb\nc\nd\n");
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "A");
+
+    let checkpoint = rewriter.checkpoint();
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "B");
+    let _: String = rewriter.rewrite(&span((4, 0), (4, 1)), "D");
+    rewriter.rollback_to(checkpoint.clone());
+
+    // smoelius: After the rollback, rewrites may again start before the rolled back ones.
+    let _: String = rewriter.rewrite(&span((3, 0), (3, 1)), "C");
+    rewriter.rollback_to(checkpoint);
+
+    assert_eq!(
+        String::from("b"),
+        rewriter.rewrite(&span((2, 0), (2, 1)), "β")
+    );
+
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("A\nβ\nc\nd\n", contents);
+    assert_eq!(2, source_map.edits().len());
+}
+
+#[test]
+#[should_panic = "checkpoint is ahead of the `Rewriter`"]
+fn rollback_to_later_checkpoint() {
+    let mut rewriter = Rewriter::new("a\nb\n");
+    let earlier = rewriter.checkpoint();
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "A");
+    let later = rewriter.checkpoint();
+    rewriter.rollback_to(earlier);
+    rewriter.rollback_to(later);
+}
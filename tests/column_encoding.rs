@@ -0,0 +1,200 @@
+mod common;
+
+use common::{line_column, span};
+use rewriter::{
+    ColumnEncoding, LineIndex, OffsetCalculator, OwnedRewriter, Rewriter, SharedOffsetCalculator,
+    Span,
+};
+use std::num::NonZeroUsize;
+
+const VISUAL: ColumnEncoding = ColumnEncoding::Visual {
+    tab_width: NonZeroUsize::new(4).unwrap(),
+};
+
+// smoelius: `é` is two bytes and one UTF-16 code unit. `𝄞` is four bytes and two UTF-16 code
+// units. `e\u{301}` is one grapheme but two chars.
+
+#[test]
+fn chars() {
+    let mut offset_calculator = OffsetCalculator::<Span>::new("aé𝄞b");
+    assert_eq!(
+        (1, 7),
+        offset_calculator.offsets_from_span(&span((1, 1), (1, 3)))
+    );
+}
+
+#[test]
+fn bytes() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_encoding("aé𝄞b", ColumnEncoding::Bytes);
+    assert_eq!(
+        (1, 7),
+        offset_calculator.offsets_from_span(&span((1, 1), (1, 7)))
+    );
+    assert_eq!(
+        line_column(1, 7),
+        offset_calculator.line_column_from_offset(7)
+    );
+}
+
+#[test]
+fn bytes_inside_char_rounds_down() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_encoding("aé𝄞b", ColumnEncoding::Bytes);
+    assert_eq!(
+        (1, 3),
+        offset_calculator.offsets_from_span(&span((1, 2), (1, 5)))
+    );
+    assert_eq!(
+        line_column(1, 3),
+        offset_calculator.line_column_from_offset(5)
+    );
+}
+
+#[test]
+fn utf16() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_encoding("aé𝄞b\nc", ColumnEncoding::Utf16);
+    assert_eq!(
+        (3, 8),
+        offset_calculator.offsets_from_span(&span((1, 2), (1, 5)))
+    );
+    assert_eq!(
+        (9, 10),
+        offset_calculator.offsets_from_span(&span((2, 0), (2, 1)))
+    );
+    assert_eq!(
+        line_column(1, 4),
+        offset_calculator.line_column_from_offset(7)
+    );
+}
+
+#[test]
+fn utf16_inside_surrogate_pair_rounds_down() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_encoding("a𝄞b", ColumnEncoding::Utf16);
+    assert_eq!(
+        (1, 1),
+        offset_calculator.offsets_from_span(&span((1, 1), (1, 2)))
+    );
+    assert_eq!(
+        line_column(1, 1),
+        offset_calculator.line_column_from_offset(3)
+    );
+}
+
+#[test]
+fn utf16_past_end_of_line() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_encoding("𝄞\nb", ColumnEncoding::Utf16);
+    assert_eq!(
+        Err(rewriter::RewriteError::ColumnPastEndOfLine {
+            line_column: line_column(1, 3),
+            end_of_line: line_column(1, 2),
+        }),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 3)))
+    );
+    assert_eq!(
+        Ok((0, 4)),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 2)))
+    );
+}
+
+#[cfg(feature = "graphemes")]
+#[test]
+fn graphemes() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_encoding("ae\u{301}b", ColumnEncoding::Graphemes);
+    assert_eq!(
+        (1, 4),
+        offset_calculator.offsets_from_span(&span((1, 1), (1, 2)))
+    );
+    assert_eq!(
+        line_column(1, 1),
+        offset_calculator.line_column_from_offset(2)
+    );
+    assert_eq!(
+        line_column(1, 2),
+        offset_calculator.line_column_from_offset(4)
+    );
+}
+
+#[test]
+fn visual() {
+    let mut offset_calculator = OffsetCalculator::<Span>::with_encoding("aa\tb\n\tc", VISUAL);
+    assert_eq!(
+        (3, 4),
+        offset_calculator.offsets_from_span(&span((1, 4), (1, 5)))
+    );
+    assert_eq!(
+        (6, 7),
+        offset_calculator.offsets_from_span(&span((2, 4), (2, 5)))
+    );
+    assert_eq!(
+        line_column(1, 4),
+        offset_calculator.line_column_from_offset(3)
+    );
+}
+
+#[test]
+fn visual_inside_tab_rounds_down() {
+    let mut offset_calculator = OffsetCalculator::<Span>::with_encoding("aa\tb", VISUAL);
+    assert_eq!(
+        (2, 2),
+        offset_calculator.offsets_from_span(&span((1, 3), (1, 3)))
+    );
+}
+
+#[test]
+fn visual_past_end_of_line() {
+    let mut offset_calculator = OffsetCalculator::<Span>::with_encoding("\t\nb", VISUAL);
+    assert_eq!(
+        Err(rewriter::RewriteError::ColumnPastEndOfLine {
+            line_column: line_column(1, 5),
+            end_of_line: line_column(1, 4),
+        }),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 5)))
+    );
+}
+
+#[test]
+fn visual_rewriter() {
+    let mut rewriter = Rewriter::<Span>::with_encoding("\ta\tb", VISUAL);
+    let _: String = rewriter.rewrite(&span((1, 4), (1, 5)), "x");
+    let _: String = rewriter.rewrite(&span((1, 8), (1, 9)), "y");
+    assert_eq!("\tx\ty", rewriter.contents());
+}
+
+#[test]
+fn line_index() {
+    let line_index = LineIndex::with_encoding("aé\n𝄞b", ColumnEncoding::Utf16);
+    assert_eq!(ColumnEncoding::Utf16, line_index.encoding());
+    assert_eq!(
+        Ok(8),
+        line_index.offset_from_line_column("aé\n𝄞b", line_column(2, 2))
+    );
+}
+
+#[test]
+fn shared_offset_calculator() {
+    let offset_calculator =
+        SharedOffsetCalculator::<Span>::with_encoding("aé𝄞b", ColumnEncoding::Bytes);
+    assert_eq!(
+        (3, 7),
+        offset_calculator.offsets_from_span(&span((1, 3), (1, 7)))
+    );
+}
+
+#[test]
+fn rewriter() {
+    let mut rewriter = Rewriter::<Span>::with_encoding("aé𝄞b", ColumnEncoding::Utf16);
+    let _: String = rewriter.rewrite(&span((1, 2), (1, 4)), "x");
+    assert_eq!("aéxb", rewriter.contents());
+}
+
+#[test]
+fn owned_rewriter() {
+    let mut rewriter = OwnedRewriter::<Span>::with_encoding("aé𝄞b", ColumnEncoding::Bytes);
+    let _: String = rewriter.rewrite(&span((1, 1), (1, 3)), "e");
+    assert_eq!("ae𝄞b", rewriter.contents());
+}
@@ -0,0 +1,11 @@
+#![allow(dead_code)]
+
+use rewriter::{LineColumn, Span};
+
+pub fn line_column(line: usize, column: usize) -> LineColumn {
+    LineColumn { line, column }
+}
+
+pub fn span(start: (usize, usize), end: (usize, usize)) -> Span {
+    Span::new(line_column(start.0, start.1), line_column(end.0, end.1))
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use assert_cmd::assert::OutputAssertExt;
+use common::span;
+use rewriter::{LineTerminators, Rewriter, diff::Line};
+use std::{
+    fs::{read_to_string, write},
+    process::Command,
+};
+use tempfile::tempdir;
+
+const ORIGINAL: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12";
+
+#[test]
+fn diff() {
+    let mut rewriter = Rewriter::new(ORIGINAL);
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "two");
+    let _: String = rewriter.rewrite(&span((3, 0), (4, 0)), "");
+    let _: String = rewriter.rewrite(&span((12, 0), (12, 2)), "twelve\n");
+
+    assert_eq!(
+        "\
+--- a/src/numbers.txt
++++ b/src/numbers.txt
+@@ -1,5 +1,4 @@
+ 1
+-2
+-3
++two
+ 4
+ 5
+@@ -10,3 +9,3 @@
+ 10
+ 11
+-12
+\\ No newline at end of file
++twelve
+",
+        rewriter.diff("src/numbers.txt", 2).to_string()
+    );
+
+    let diff = rewriter.diff("src/numbers.txt", 4);
+    assert_eq!(1, diff.hunks().len());
+    assert_eq!(
+        "\
+@@ -1,12 +1,11 @@
+ 1
+-2
+-3
++two
+ 4
+ 5
+ 6
+ 7
+ 8
+ 9
+ 10
+ 11
+-12
+\\ No newline at end of file
++twelve
+",
+        diff.hunks()[0].to_string()
+    );
+}
+
+#[test]
+fn unchanged() {
+    let mut rewriter = Rewriter::new(ORIGINAL);
+    let _: String = rewriter.rewrite(&span((5, 0), (5, 1)), "5");
+    let diff = rewriter.diff("numbers.txt", 3);
+    assert!(diff.is_empty());
+    assert_eq!("", diff.to_string());
+}
+
+#[test]
+fn git_apply() {
+    let mut rewriter = Rewriter::new(ORIGINAL);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 0)), "0\n");
+    let _: String = rewriter.rewrite(&span((6, 1), (6, 1)), "6");
+    let _: String = rewriter.rewrite(&span((7, 0), (9, 1)), "seven to nine");
+    let _: String = rewriter.rewrite(&span((12, 2), (12, 2)), "\n");
+    let diff = rewriter.diff("numbers.txt", 1);
+    let contents = rewriter.contents();
+
+    let tempdir = tempdir().unwrap();
+    let path = tempdir.path().join("numbers.txt");
+    write(&path, ORIGINAL).unwrap();
+    write(tempdir.path().join("numbers.diff"), diff.to_string()).unwrap();
+
+    Command::new("git")
+        .args(["apply", "numbers.diff"])
+        .current_dir(&tempdir)
+        .assert()
+        .success();
+
+    assert_eq!(contents, read_to_string(&path).unwrap());
+}
+
+#[test]
+fn line_terminators() {
+    for (terminator, line_terminators) in [
+        ("\r", LineTerminators::CrLfCrlf),
+        ("\u{2028}", LineTerminators::Unicode),
+    ] {
+        let original = ["a", "b", "c", "d"].join(terminator);
+        let mut rewriter = Rewriter::with_format(&original, line_terminators.into());
+        let _: String = rewriter.rewrite(&span((3, 0), (3, 1)), "C");
+
+        let diff = rewriter.diff("x", 0);
+        let [hunk] = diff.hunks() else {
+            panic!("{diff:?}");
+        };
+        assert_eq!(
+            (3, 1, 3, 1),
+            (
+                hunk.original_start,
+                hunk.original_len,
+                hunk.rewritten_start,
+                hunk.rewritten_len
+            )
+        );
+        assert_eq!(
+            vec![
+                Line::Removed(format!("c{terminator}")),
+                Line::Added(format!("C{terminator}")),
+            ],
+            hunk.lines
+        );
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::span;
+use rewriter::{EditSet, interface::Span as _};
+
+#[test]
+fn out_of_order() {
+    let mut edit_set = EditSet::new();
+    edit_set.insert(span((2, 4), (2, 5)), "z");
+    edit_set.insert(span((1, 0), (1, 3)), "let");
+    edit_set.insert(span((2, 0), (2, 0)), "// ");
+    edit_set.insert(span((2, 0), (2, 0)), "x: ");
+    assert!(edit_set.overlaps().is_empty());
+    assert_eq!(
+        "let x = 0;\n// x: y = z;\n",
+        edit_set.apply("var x = 0;\ny = 1;\n").unwrap()
+    );
+}
+
+#[test]
+fn overlap() {
+    let mut edit_set = EditSet::new();
+    edit_set.insert(span((1, 6), (1, 7)), "");
+    edit_set.insert(span((1, 0), (1, 9)), "");
+    edit_set.insert(span((1, 2), (1, 3)), "");
+    edit_set.insert(span((1, 9), (1, 9)), "");
+
+    let overlaps = edit_set.overlaps();
+    assert_eq!(2, overlaps.len());
+    for overlap in &overlaps {
+        assert_eq!(span((1, 0), (1, 9)).end(), overlap.first.end());
+    }
+
+    assert!(edit_set.apply("var x = 0;").is_err());
+}
+
+#[test]
+fn nested_overlaps() {
+    let mut edit_set = EditSet::new();
+    edit_set.insert(span((1, 0), (1, 5)), "");
+    edit_set.insert(span((1, 1), (1, 3)), "");
+    edit_set.insert(span((1, 2), (1, 4)), "");
+
+    let overlaps = edit_set
+        .overlaps()
+        .into_iter()
+        .map(|overlap| (overlap.first, overlap.second))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![
+            (span((1, 0), (1, 5)), span((1, 1), (1, 3))),
+            (span((1, 0), (1, 5)), span((1, 2), (1, 4))),
+            (span((1, 1), (1, 3)), span((1, 2), (1, 4))),
+        ],
+        overlaps
+    );
+}
@@ -0,0 +1,145 @@
+#![cfg(feature = "proc-macro2-impl")]
+
+use proc_macro2::{Span, TokenStream};
+use rewriter::{RewriteError, Rewriter};
+use std::str::FromStr;
+
+fn spans(text: &str) -> Vec<Span> {
+    TokenStream::from_str(text)
+        .unwrap()
+        .into_iter()
+        .map(|tree| tree.span())
+        .collect()
+}
+
+#[test]
+fn foreign_span() {
+    let span = spans("foo")[0];
+    let mut rewriter = Rewriter::<Span>::new("bar");
+    assert_eq!(
+        Err(RewriteError::ForeignSpan {
+            start: span.start(),
+            end: span.end()
+        }),
+        rewriter.try_rewrite(&span, "baz")
+    );
+    let span = spans("bar")[0];
+    assert_eq!(Ok(String::from("bar")), rewriter.try_rewrite(&span, "baz"));
+    assert_eq!("baz", rewriter.contents());
+}
+
+// smoelius: The two texts are identical, so the spans cannot be told apart by the text they cover.
+#[test]
+fn same_text() {
+    const ORIGINAL: &str = "fn a() {}\n";
+
+    let spans_a = spans(ORIGINAL);
+    let spans_b = spans(ORIGINAL);
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    assert_eq!(
+        Ok(String::from("fn")),
+        rewriter.try_rewrite(&spans_a[0], "pub fn")
+    );
+    assert_eq!(
+        Err(RewriteError::ForeignSpan {
+            start: spans_b[1].start(),
+            end: spans_b[1].end()
+        }),
+        rewriter.try_rewrite(&spans_b[1], "b")
+    );
+    assert_eq!(
+        Ok(String::from("a")),
+        rewriter.try_rewrite(&spans_a[1], "c")
+    );
+    assert_eq!("pub fn c() {}\n", rewriter.contents());
+}
+
+#[test]
+fn set_source_span() {
+    const ORIGINAL: &str = "fn a() {}\n";
+
+    let spans_a = spans(ORIGINAL);
+    let spans_b = spans(ORIGINAL);
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    rewriter.set_source_span(spans_a[0]);
+    assert_eq!(
+        Err(RewriteError::ForeignSpan {
+            start: spans_b[0].start(),
+            end: spans_b[0].end()
+        }),
+        rewriter.try_rewrite(&spans_b[0], "pub fn")
+    );
+}
+
+#[test]
+fn call_site() {
+    let mut rewriter = Rewriter::<Span>::new("bar");
+    assert_eq!(
+        Err(RewriteError::NoLocation),
+        rewriter.try_rewrite(&Span::call_site(), "baz")
+    );
+}
+
+#[test]
+fn call_site_after_rewrite() {
+    const ORIGINAL: &str = "fn a() {}\n";
+
+    let spans = spans(ORIGINAL);
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    assert_eq!(Ok(String::from("a")), rewriter.try_rewrite(&spans[1], "b"));
+    assert_eq!(
+        Err(RewriteError::NoLocation),
+        rewriter.try_rewrite(&Span::call_site(), "c")
+    );
+    assert_eq!("fn b() {}\n", rewriter.contents());
+}
+
+#[test]
+fn foreign_span_after_rewrite() {
+    const ORIGINAL: &str = "fn a() {}\n";
+
+    let spans_a = spans(ORIGINAL);
+    let spans_b = spans(ORIGINAL);
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    assert_eq!(
+        Ok(String::from("a")),
+        rewriter.try_rewrite(&spans_a[1], "b")
+    );
+    assert_eq!(
+        Err(RewriteError::ForeignSpan {
+            start: spans_b[0].start(),
+            end: spans_b[0].end()
+        }),
+        rewriter.try_rewrite(&spans_b[0], "pub fn")
+    );
+    assert_eq!("fn b() {}\n", rewriter.contents());
+}
+
+#[test]
+#[should_panic = "span does not belong to the original text"]
+fn rewrite() {
+    const ORIGINAL: &str = "fn a() {}\n";
+
+    let spans_a = spans(ORIGINAL);
+    let spans_b = spans(ORIGINAL);
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    let _: String = rewriter.rewrite(&spans_a[0], "pub fn");
+    let _: String = rewriter.rewrite(&spans_b[1], "b");
+}
+
+#[test]
+#[should_panic = "span has no location"]
+fn rewrite_call_site_after_rewrite() {
+    const ORIGINAL: &str = "fn a() {}\n";
+
+    let spans = spans(ORIGINAL);
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    let _: String = rewriter.rewrite(&spans[1], "b");
+    let _: String = rewriter.rewrite(&Span::call_site(), "c");
+}
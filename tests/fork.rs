@@ -0,0 +1,31 @@
+mod common;
+
+use common::span;
+use rewriter::Rewriter;
+
+#[test]
+fn fork() {
+    let mut rewriter = Rewriter::new("a + b\nc - d\n");
+    let _: String = rewriter.rewrite(&span((1, 2), (1, 3)), "-");
+
+    let mut fork = rewriter.fork();
+    let _: String = fork.rewrite(&span((2, 2), (2, 3)), "+");
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "e");
+
+    assert_eq!("a - b\nc + d\n", fork.contents());
+    assert_eq!("a - b\ne - d\n", rewriter.contents());
+}
+
+#[test]
+fn variants() {
+    let spans = [
+        span((2, 2), (2, 3)),
+        span((1, 2), (1, 3)),
+        span((2, 2), (2, 3)),
+    ];
+    let edits = [(&spans[0], "+"), (&spans[1], "-"), (&spans[2], "*")];
+    assert_eq!(
+        vec!["a + b\nc + d\n", "a - b\nc - d\n", "a + b\nc * d\n"],
+        Rewriter::variants("a + b\nc - d\n", edits)
+    );
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use common::line_column;
+use rewriter::{LineColumn, OffsetCalculator, OwnedOffsetCalculator, SharedOffsetCalculator, Span};
+
+#[test]
+fn line_column_from_offset() {
+    let offset_calculator = OffsetCalculator::<Span>::new("ab\nαβ\n");
+    assert_eq!(
+        line_column(1, 0),
+        offset_calculator.line_column_from_offset(0)
+    );
+    assert_eq!(
+        line_column(1, 2),
+        offset_calculator.line_column_from_offset(2)
+    );
+    assert_eq!(
+        line_column(2, 0),
+        offset_calculator.line_column_from_offset(3)
+    );
+    assert_eq!(
+        line_column(2, 1),
+        offset_calculator.line_column_from_offset(5)
+    );
+    assert_eq!(
+        line_column(3, 0),
+        offset_calculator.line_column_from_offset(8)
+    );
+}
+
+#[test]
+fn inside_char_rounds_down() {
+    let offset_calculator = OffsetCalculator::<Span>::new("aαb");
+    assert_eq!(
+        line_column(1, 1),
+        offset_calculator.line_column_from_offset(2)
+    );
+    assert_eq!(
+        line_column(1, 2),
+        offset_calculator.line_column_from_offset(3)
+    );
+}
+
+#[test]
+fn round_trip() {
+    let original = "fn main() {\n    let α = \"β\";\n}\n";
+    let mut offset_calculator = OffsetCalculator::<Span>::new(original);
+    for (offset, _) in original.char_indices() {
+        let span = offset_calculator.span_from_offsets(offset, original.len());
+        assert_eq!(
+            (offset, original.len()),
+            offset_calculator.offsets_from_span(&span)
+        );
+    }
+}
+
+#[test]
+fn span_from_offsets() {
+    let original = "let α = 1;\nlet β = 2;\n";
+    let start = original.find("β").unwrap();
+    let end = start + "β = 2".len();
+
+    let offset_calculator = SharedOffsetCalculator::new(original);
+    assert_eq!(
+        Span::new(line_column(2, 4), line_column(2, 9)),
+        offset_calculator.span_from_offsets(start, end)
+    );
+
+    let offset_calculator = OwnedOffsetCalculator::new(original);
+    assert_eq!(
+        Span::new(line_column(2, 4), line_column(2, 9)),
+        offset_calculator.span_from_offsets(start, end)
+    );
+}
+
+#[test]
+#[should_panic = "offset 4 is past the end of the original text"]
+fn past_end() {
+    let offset_calculator = OffsetCalculator::<Span>::new("abc");
+    let _: LineColumn = offset_calculator.line_column_from_offset(4);
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::span;
+use rewriter::{
+    Edit, LineEnding, LineEndingPolicy, LineTerminators, OwnedRewriter, Rewriter, Span,
+    TrailingNewline,
+};
+
+const NORMALIZE: LineEndingPolicy = LineEndingPolicy {
+    normalize_replacements: true,
+    trailing_newline: TrailingNewline::Unchanged,
+};
+
+#[test]
+fn detect() {
+    assert_eq!(Some(LineEnding::Crlf), LineEnding::detect("a\r\nb\r\nc\n"));
+    assert_eq!(Some(LineEnding::Lf), LineEnding::detect("a\nb"));
+    assert_eq!(Some(LineEnding::Lf), LineEnding::detect("a\r\nb\n"));
+    assert_eq!(None, LineEnding::detect("a\rb"));
+}
+
+#[test]
+fn normalize_to_crlf() {
+    let mut rewriter = Rewriter::<Span>::new("a\r\nb\r\n");
+    rewriter.set_line_ending_policy(NORMALIZE);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x\ny\r\nz");
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("x\r\ny\r\nz\r\nb\r\n", contents);
+    assert_eq!(
+        &[Edit {
+            original: 0..1,
+            rewritten: 0..7
+        }],
+        source_map.edits()
+    );
+}
+
+#[test]
+fn normalize_to_lf() {
+    let mut rewriter = Rewriter::<Span>::new("a\nb\n");
+    rewriter.set_line_ending_policy(NORMALIZE);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x\r\ny");
+    assert_eq!("x\ny\nb\n", rewriter.contents());
+}
+
+#[test]
+fn without_normalization() {
+    let mut rewriter = Rewriter::<Span>::new("a\r\nb\r\n");
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x\ny");
+    assert_eq!("x\ny\r\nb\r\n", rewriter.contents());
+}
+
+#[test]
+fn ensure_trailing_newline() {
+    let mut rewriter = Rewriter::<Span>::new("a\r\nb");
+    rewriter.set_line_ending_policy(LineEndingPolicy {
+        normalize_replacements: false,
+        trailing_newline: TrailingNewline::Ensure,
+    });
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x");
+    assert_eq!("x\r\nb\r\n", rewriter.preview());
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("x\r\nb\r\n", contents);
+    assert_eq!(
+        &[
+            Edit {
+                original: 0..1,
+                rewritten: 0..1
+            },
+            Edit {
+                original: 4..4,
+                rewritten: 4..6
+            },
+        ],
+        source_map.edits()
+    );
+}
+
+#[test]
+fn keep_trailing_newline() {
+    let policy = LineEndingPolicy {
+        normalize_replacements: false,
+        trailing_newline: TrailingNewline::Keep,
+    };
+
+    let mut rewriter = Rewriter::<Span>::new("a\nb\n");
+    rewriter.set_line_ending_policy(policy);
+    let _: String = rewriter.rewrite(&span((2, 0), (3, 0)), "");
+    assert_eq!("a\n", rewriter.contents());
+
+    let mut rewriter = Rewriter::<Span>::new("a\nb");
+    rewriter.set_line_ending_policy(policy);
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "c\n");
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("a\nc", contents);
+    assert_eq!(
+        &[Edit {
+            original: 2..3,
+            rewritten: 2..3
+        }],
+        source_map.edits()
+    );
+}
+
+#[test]
+fn keep_removes_original_trailing_newline() {
+    let mut rewriter = Rewriter::<Span>::new("a\nb");
+    rewriter.set_line_ending_policy(LineEndingPolicy {
+        normalize_replacements: false,
+        trailing_newline: TrailingNewline::Keep,
+    });
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "");
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("a", contents);
+    assert_eq!(
+        &[Edit {
+            original: 1..3,
+            rewritten: 1..1
+        }],
+        source_map.edits()
+    );
+}
+
+const KEEP: LineEndingPolicy = LineEndingPolicy {
+    normalize_replacements: false,
+    trailing_newline: TrailingNewline::Keep,
+};
+
+#[test]
+fn keep_removes_crlf_whole() {
+    let mut rewriter = Rewriter::<Span>::new("ab");
+    rewriter.set_line_ending_policy(KEEP);
+    let _: String = rewriter.rewrite(&span((1, 1), (1, 2)), "c\r\n");
+    assert_eq!("ac", rewriter.contents());
+}
+
+// smoelius: The first replacement ends with the `\r` of the final `\r\n`. Removing just the `\n`
+// would leave a lone `\r`.
+#[test]
+fn keep_does_not_split_crlf() {
+    let mut rewriter = Rewriter::<Span>::new("ab");
+    rewriter.set_line_ending_policy(KEEP);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x\r");
+    let _: String = rewriter.rewrite(&span((1, 1), (1, 2)), "\n");
+    assert_eq!("x\r\n", rewriter.contents());
+}
+
+#[test]
+fn keep_with_line_terminators() {
+    let mut rewriter = Rewriter::<Span>::with_format("a", LineTerminators::CrLfCrlf.into());
+    rewriter.set_line_ending_policy(KEEP);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "b\r");
+    assert_eq!("b", rewriter.contents());
+
+    let mut rewriter = Rewriter::<Span>::with_format("a\r", LineTerminators::CrLfCrlf.into());
+    rewriter.set_line_ending_policy(KEEP);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "b");
+    assert_eq!("b\r", rewriter.contents());
+}
+
+#[test]
+fn owned_rewriter() {
+    let mut rewriter = OwnedRewriter::<Span>::new("a\r\nb\r\n");
+    rewriter.set_line_ending_policy(NORMALIZE);
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "c\nd");
+    assert_eq!("a\r\nc\r\nd\r\n", rewriter.contents());
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use common::line_column;
+use rewriter::{LineIndex, OffsetCalculator, RewriteError, Span};
+
+#[test]
+fn lines() {
+    let text = "ab\nαβ\n";
+    let line_index = LineIndex::new(text);
+    assert_eq!(3, line_index.line_count());
+    assert_eq!(Some(0..2), line_index.line_range(1));
+    assert_eq!(Some(3..7), line_index.line_range(2));
+    assert_eq!(Some(8..8), line_index.line_range(3));
+    assert_eq!(None, line_index.line_range(0));
+    assert_eq!(None, line_index.line_range(4));
+    assert_eq!(Some(true), line_index.is_ascii_line(1));
+    assert_eq!(Some(false), line_index.is_ascii_line(2));
+}
+
+#[test]
+fn line_from_offset() {
+    let line_index = LineIndex::new("ab\nc\n");
+    assert_eq!(Some(1), line_index.line_from_offset(0));
+    assert_eq!(Some(1), line_index.line_from_offset(2));
+    assert_eq!(Some(2), line_index.line_from_offset(3));
+    assert_eq!(Some(3), line_index.line_from_offset(5));
+    assert_eq!(None, line_index.line_from_offset(6));
+}
+
+#[test]
+fn offset_from_line_column() {
+    let text = "ab\nαβ\nc";
+    let line_index = LineIndex::new(text);
+
+    // smoelius: Queries need not be in order.
+    assert_eq!(
+        Ok(8),
+        line_index.offset_from_line_column(text, line_column(3, 0))
+    );
+    assert_eq!(
+        Ok(5),
+        line_index.offset_from_line_column(text, line_column(2, 1))
+    );
+    assert_eq!(
+        Ok(1),
+        line_index.offset_from_line_column(text, line_column(1, 1))
+    );
+    assert_eq!(
+        Ok(9),
+        line_index.offset_from_line_column(text, line_column(3, 1))
+    );
+
+    assert_eq!(
+        Err(RewriteError::ColumnPastEndOfLine {
+            line_column: line_column(2, 3),
+            end_of_line: line_column(2, 2),
+        }),
+        line_index.offset_from_line_column(text, line_column(2, 3))
+    );
+    assert_eq!(
+        Err(RewriteError::PastEndOfFile {
+            line_column: line_column(3, 2),
+            end_of_file: line_column(3, 1),
+        }),
+        line_index.offset_from_line_column(text, line_column(3, 2))
+    );
+    assert_eq!(
+        Err(RewriteError::LineOutOfBounds {
+            line_column: line_column(4, 0),
+        }),
+        line_index.offset_from_line_column(text, line_column(4, 0))
+    );
+}
+
+#[test]
+fn offset_calculator_out_of_order() {
+    let text = "ab\nαβ\nc";
+    let mut offset_calculator = OffsetCalculator::new(text);
+    let span = |start, end| Span::new(start, end);
+
+    assert_eq!(
+        (8, 9),
+        offset_calculator.offsets_from_span(&span(line_column(3, 0), line_column(3, 1)))
+    );
+    assert_eq!(
+        (0, 5),
+        offset_calculator.offsets_from_span(&span(line_column(1, 0), line_column(2, 1)))
+    );
+}
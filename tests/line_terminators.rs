@@ -0,0 +1,142 @@
+mod common;
+
+use common::{line_column, span};
+use rewriter::{
+    ColumnEncoding, LineIndex, LineTerminators, OffsetCalculator, OwnedRewriter, RewriteError,
+    Rewriter, SharedOffsetCalculator, Span, TextFormat,
+};
+
+#[test]
+fn lf() {
+    let mut offset_calculator = OffsetCalculator::<Span>::new("ab\r\ncd");
+    assert_eq!(
+        (0, 3),
+        offset_calculator.offsets_from_span(&span((1, 0), (1, 3)))
+    );
+}
+
+#[test]
+fn lf_crlf() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_format("ab\r\ncd\ne", LineTerminators::LfCrlf.into());
+    assert_eq!(
+        Ok((0, 2)),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 2)))
+    );
+    assert_eq!(
+        Err(RewriteError::ColumnPastEndOfLine {
+            line_column: line_column(1, 3),
+            end_of_line: line_column(1, 2),
+        }),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 3)))
+    );
+    assert_eq!(
+        (4, 7),
+        offset_calculator.offsets_from_span(&span((2, 0), (3, 0)))
+    );
+    assert_eq!(
+        line_column(1, 2),
+        offset_calculator.line_column_from_offset(3)
+    );
+    assert_eq!(
+        line_column(2, 0),
+        offset_calculator.line_column_from_offset(4)
+    );
+}
+
+#[test]
+fn lf_crlf_lone_cr() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_format("a\rb\nc", LineTerminators::LfCrlf.into());
+    assert_eq!(
+        (0, 3),
+        offset_calculator.offsets_from_span(&span((1, 0), (1, 3)))
+    );
+}
+
+#[test]
+fn cr_lf_crlf() {
+    let mut offset_calculator =
+        OffsetCalculator::<Span>::with_format("a\rb\r\nc\nd", LineTerminators::CrLfCrlf.into());
+    assert_eq!(
+        (2, 5),
+        offset_calculator.offsets_from_span(&span((2, 0), (3, 0)))
+    );
+    assert_eq!(
+        (5, 7),
+        offset_calculator.offsets_from_span(&span((3, 0), (4, 0)))
+    );
+    assert_eq!(
+        line_column(1, 1),
+        offset_calculator.line_column_from_offset(1)
+    );
+}
+
+#[test]
+fn unicode() {
+    let mut offset_calculator = OffsetCalculator::<Span>::with_format(
+        "a\u{2028}b\u{2029}c\rd",
+        LineTerminators::Unicode.into(),
+    );
+    assert_eq!(
+        (4, 8),
+        offset_calculator.offsets_from_span(&span((2, 0), (3, 0)))
+    );
+    assert_eq!(
+        (8, 10),
+        offset_calculator.offsets_from_span(&span((3, 0), (4, 0)))
+    );
+    assert_eq!(
+        line_column(1, 1),
+        offset_calculator.line_column_from_offset(2)
+    );
+}
+
+#[test]
+fn with_column_encoding() {
+    let format = TextFormat {
+        column_encoding: ColumnEncoding::Utf16,
+        line_terminators: LineTerminators::LfCrlf,
+    };
+    let offset_calculator = SharedOffsetCalculator::<Span>::with_format("𝄞\r\nb", format);
+    assert_eq!(
+        (0, 4),
+        offset_calculator.offsets_from_span(&span((1, 0), (1, 2)))
+    );
+    assert_eq!(
+        (6, 7),
+        offset_calculator.offsets_from_span(&span((2, 0), (2, 1)))
+    );
+}
+
+#[test]
+fn line_index() {
+    let text = "ab\r\ncd\r\n";
+    let line_index = LineIndex::with_format(text, LineTerminators::LfCrlf.into());
+    assert_eq!(3, line_index.line_count());
+    assert_eq!(Some(0..2), line_index.line_range(1));
+    assert_eq!(Some(4..6), line_index.line_range(2));
+    assert_eq!(Some(8..8), line_index.line_range(3));
+    assert_eq!(Some(1), line_index.line_from_offset(3));
+    assert_eq!(
+        Ok(6),
+        line_index.offset_from_line_column(text, line_column(2, 2))
+    );
+}
+
+#[test]
+fn rewriter() {
+    let mut rewriter =
+        Rewriter::<Span>::with_format("ab\r\ncd\r\n", LineTerminators::LfCrlf.into());
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 2)), "x");
+    let _: String = rewriter.rewrite(&span((2, 2), (3, 0)), "\n");
+    assert_eq!("x\r\ncd\n", rewriter.contents());
+}
+
+#[test]
+fn owned_rewriter() {
+    let mut rewriter =
+        OwnedRewriter::<Span>::with_format("a\rb\rc", LineTerminators::CrLfCrlf.into());
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "x");
+    assert_eq!("a\rx\rc", rewriter.contents());
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "proc-macro2-impl")]
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use rewriter::{MultiFileRewriter, RewriteError};
+use std::path::{Path, PathBuf};
+
+fn spans(tokens: TokenStream) -> Vec<Span> {
+    tokens
+        .into_iter()
+        .map(|tree: TokenTree| tree.span())
+        .collect()
+}
+
+#[test]
+fn multi_file_rewriter() {
+    let mut rewriter = MultiFileRewriter::new();
+    let a = spans(rewriter.parse("a.rs", "fn a() {}\n").unwrap());
+    let b = spans(rewriter.parse("b.rs", "fn b() {}\n").unwrap());
+    let _ = rewriter.parse("c.rs", "// no tokens\n").unwrap();
+
+    assert_eq!(Some(Path::new("b.rs")), rewriter.path(b[0]));
+
+    // smoelius: Spans of different files may be interleaved.
+    assert_eq!("fn", rewriter.rewrite(a[0], "pub fn"));
+    assert_eq!("b", rewriter.rewrite(b[1], "beta"));
+    assert_eq!("a", rewriter.rewrite(a[1], "alpha"));
+
+    let contents = rewriter.contents();
+    assert_eq!(
+        vec![
+            (PathBuf::from("a.rs"), String::from("pub fn alpha() {}\n")),
+            (PathBuf::from("b.rs"), String::from("fn beta() {}\n")),
+            (PathBuf::from("c.rs"), String::from("// no tokens\n")),
+        ],
+        contents.into_iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn foreign_span() {
+    let mut rewriter = MultiFileRewriter::new();
+    let _ = rewriter.parse("a.rs", "fn a() {}").unwrap();
+    let span = spans("fn b() {}".parse().unwrap())[0];
+    assert_eq!(None, rewriter.path(span));
+    assert_eq!(
+        Err(RewriteError::ForeignSpan {
+            start: span.start(),
+            end: span.end()
+        }),
+        rewriter.try_rewrite(span, "x")
+    );
+    assert_eq!(
+        Err(RewriteError::NoLocation),
+        rewriter.try_rewrite(Span::call_site(), "x")
+    );
+}
+
+#[test]
+#[should_panic = "span has no location"]
+fn call_site() {
+    let mut rewriter = MultiFileRewriter::new();
+    let _ = rewriter.parse("a.rs", "fn a() {}").unwrap();
+    let _: String = rewriter.rewrite(Span::call_site(), "x");
+}
+
+#[test]
+#[should_panic = "a.rs was already added"]
+fn duplicate_path() {
+    let mut rewriter = MultiFileRewriter::new();
+    let _ = rewriter.parse("a.rs", "fn a() {}").unwrap();
+    let _ = rewriter.parse("a.rs", "fn a() {}").unwrap();
+}
@@ -0,0 +1,77 @@
+mod common;
+
+use common::span;
+use rewriter::{LineColumn, OwnedOffsetCalculator, OwnedRewriter, RewriteError, Span};
+
+fn rewriter_for(path_contents: &str) -> OwnedRewriter<Span> {
+    // smoelius: The `String` is dropped when this function returns.
+    OwnedRewriter::new(path_contents.to_owned())
+}
+
+#[test]
+fn owned_rewriter() {
+    let mut rewriter = rewriter_for("a + b\nc - d\n");
+    assert_eq!("+", rewriter.rewrite(&span((1, 2), (1, 3)), "-"));
+    assert_eq!("-", rewriter.rewrite(&span((2, 2), (2, 3)), "+"));
+    assert_eq!("a - b\nc + d\n", rewriter.preview());
+    assert_eq!("a + b\nc - d\n", &**rewriter.original());
+    assert_eq!("a - b\nc + d\n", rewriter.contents());
+}
+
+#[test]
+fn send() {
+    let mut rewriter = OwnedRewriter::new("a + b\n");
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x");
+
+    let handle = std::thread::spawn(move || {
+        let _: String = rewriter.rewrite(&span((1, 4), (1, 5)), "y");
+        rewriter
+    });
+
+    let rewriter = handle.join().unwrap();
+    assert_eq!("x + y\n", rewriter.contents());
+}
+
+#[test]
+fn try_rewrite() {
+    let mut rewriter = OwnedRewriter::new("ab\nc");
+    assert_eq!(
+        Err(RewriteError::LineOutOfBounds {
+            line_column: LineColumn { line: 3, column: 0 }
+        }),
+        rewriter.try_rewrite(&span((2, 0), (3, 0)), "")
+    );
+    assert_eq!(
+        Ok("c".to_owned()),
+        rewriter.try_rewrite(&span((2, 0), (2, 1)), "d")
+    );
+    assert_eq!("ab\nd", rewriter.contents());
+}
+
+#[test]
+fn checkpoint() {
+    let mut rewriter = OwnedRewriter::new("a b c\n");
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "x");
+    let checkpoint = rewriter.checkpoint();
+    let _: String = rewriter.rewrite(&span((1, 2), (1, 3)), "y");
+    rewriter.rollback_to(checkpoint);
+    let _: String = rewriter.rewrite(&span((1, 4), (1, 5)), "z");
+    assert_eq!("x b z\n", rewriter.contents());
+}
+
+#[test]
+fn offset_calculator() {
+    let mut offset_calculator = OwnedOffsetCalculator::new("αβ\nγ\n");
+    assert_eq!(
+        (5, 7),
+        offset_calculator.offsets_from_span(&span((2, 0), (2, 1)))
+    );
+    assert_eq!(
+        (2, 4),
+        offset_calculator.offsets_from_span(&span((1, 1), (1, 2)))
+    );
+
+    let mut rewriter = OwnedRewriter::with_offset_calculator(offset_calculator);
+    let _: String = rewriter.rewrite(&span((1, 1), (2, 0)), "");
+    assert_eq!("αγ\n", rewriter.contents());
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::span;
+use rewriter::{
+    RewriteError, Rewriter,
+    patch::{ParseError, Reason, parse},
+};
+
+const ORIGINAL: &str = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n11\n12";
+
+#[test]
+fn round_trip() {
+    let mut rewriter = Rewriter::new(ORIGINAL);
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 0)), "0\n");
+    let _: String = rewriter.rewrite(&span((3, 0), (3, 1)), "three");
+    let _: String = rewriter.rewrite(&span((5, 0), (6, 0)), "");
+    let _: String = rewriter.rewrite(&span((12, 0), (12, 2)), "twelve\n");
+    let diff = rewriter.diff("numbers.txt", 1);
+    let contents = rewriter.contents();
+
+    let patches = parse(&diff.to_string()).unwrap();
+    assert_eq!(1, patches.len());
+    assert_eq!(Some("a/numbers.txt"), patches[0].original_path.as_deref());
+    assert_eq!(Some("b/numbers.txt"), patches[0].rewritten_path.as_deref());
+    assert_eq!(diff.hunks(), patches[0].hunks);
+
+    let applied = patches[0].apply(ORIGINAL);
+    assert!(applied.rejected.is_empty(), "{:#?}", applied.rejected);
+    assert_eq!(contents, applied.contents);
+}
+
+#[test]
+fn rejected() {
+    const PATCH: &str = "\
+diff --git a/numbers.txt b/numbers.txt
+--- a/numbers.txt
++++ b/numbers.txt
+@@ -2,3 +2,3 @@
+ 2
+-3
++three
+ 4
+@@ -6,2 +6,2 @@
+ six
+-7
++seven
+@@ -20 +20 @@
+-20
++twenty
+";
+
+    let patches = parse(PATCH).unwrap();
+    let applied = patches[0].apply(ORIGINAL);
+    assert_eq!(ORIGINAL.replace('3', "three"), applied.contents);
+
+    assert_eq!(2, applied.rejected.len());
+    assert_eq!(1, applied.rejected[0].index);
+    assert_eq!(
+        Reason::ContextMismatch {
+            span: span((6, 0), (8, 0))
+        },
+        applied.rejected[0].reason
+    );
+    assert_eq!(2, applied.rejected[1].index);
+    assert!(matches!(
+        applied.rejected[1].reason,
+        Reason::Rewrite(RewriteError::LineOutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn inner_context() {
+    const PATCH: &str = "\
+@@ -2,4 +2,4 @@
+-2
++two
+ 3
+ 4
+-5
++five
+";
+
+    let patches = parse(PATCH).unwrap();
+    assert_eq!(None, patches[0].original_path);
+    assert_eq!(
+        vec![(span((2, 0), (6, 0)), String::from("two\n3\n4\nfive\n"))],
+        patches[0].edits()
+    );
+
+    let applied = patches[0].apply(ORIGINAL);
+    assert!(applied.rejected.is_empty());
+    assert_eq!(
+        ORIGINAL.replacen('2', "two", 1).replacen('5', "five", 1),
+        applied.contents
+    );
+}
+
+#[test]
+fn multibyte_first_char() {
+    assert_eq!(
+        Err(ParseError::UnexpectedLine { line: 4 }),
+        parse("--- a/x\n+++ b/x\n@@ -1,1 +1,1 @@\né\n")
+    );
+}
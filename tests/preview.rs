@@ -0,0 +1,24 @@
+mod common;
+
+use common::span;
+use rewriter::Rewriter;
+
+#[test]
+fn preview() {
+    let mut rewriter = Rewriter::new("a\nb\nc\n");
+    assert_eq!("a\nb\nc\n", rewriter.preview());
+
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), "A");
+    assert_eq!("A\nb\nc\n", rewriter.preview());
+
+    let checkpoint = rewriter.checkpoint();
+    let _: String = rewriter.rewrite(&span((2, 0), (3, 0)), "");
+    assert_eq!("A\nc\n", rewriter.preview());
+
+    rewriter.rollback_to(checkpoint);
+    assert_eq!("A\nb\nc\n", rewriter.preview());
+
+    let _: String = rewriter.rewrite(&span((3, 1), (3, 1)), "C");
+    let preview = rewriter.preview();
+    assert_eq!(preview, rewriter.contents());
+}
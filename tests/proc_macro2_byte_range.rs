@@ -0,0 +1,47 @@
+#![cfg(feature = "proc-macro2-impl")]
+
+use proc_macro2::{TokenStream, TokenTree};
+use rewriter::{OffsetCalculator, Rewriter, SharedOffsetCalculator};
+use std::str::FromStr;
+
+const ORIGINAL: &str = "fn α() {\n    \"é\";\n    β\n}\n";
+
+fn leaves(tokens: TokenStream) -> Vec<TokenTree> {
+    tokens
+        .into_iter()
+        .flat_map(|tree| match tree {
+            TokenTree::Group(group) => leaves(group.stream()),
+            tree => vec![tree],
+        })
+        .collect()
+}
+
+#[test]
+fn byte_range() {
+    let tokens = TokenStream::from_str(ORIGINAL).unwrap();
+    let mut rewriter = Rewriter::<proc_macro2::Span>::new(ORIGINAL);
+    for tree in leaves(tokens) {
+        let replaced = rewriter.rewrite(&tree.span(), &tree.to_string().to_uppercase());
+        assert_eq!(tree.to_string(), replaced);
+    }
+    assert_eq!("FN Α() {\n    \"É\";\n    Β\n}\n", rewriter.contents());
+}
+
+#[test]
+fn offset_calculators() {
+    let tokens = TokenStream::from_str(ORIGINAL).unwrap();
+    let mut offset_calculator = OffsetCalculator::<proc_macro2::Span>::new(ORIGINAL);
+    let shared_offset_calculator = SharedOffsetCalculator::<proc_macro2::Span>::new(ORIGINAL);
+    for tree in leaves(tokens) {
+        let span = tree.span();
+        let range = span.byte_range();
+        assert_eq!(
+            (range.start, range.end),
+            offset_calculator.offsets_from_span(&span)
+        );
+        assert_eq!(
+            Ok((range.start, range.end)),
+            shared_offset_calculator.try_offsets_from_span(&span)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+mod common;
+
+use common::span;
+use rewriter::{
+    LineColumn, OffsetCalculator, OwnedRewriter, RewriteError, Rewriter, SharedOffsetCalculator,
+    Span,
+};
+use std::{sync::Arc, thread};
+
+#[test]
+fn send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedOffsetCalculator<Span>>();
+}
+
+#[test]
+fn parallel_rewriters() {
+    let offset_calculator = Arc::new(SharedOffsetCalculator::new("a + b\nc - d\n"));
+
+    let handles = ["x", "y", "z"]
+        .into_iter()
+        .map(|replacement| {
+            let offset_calculator = offset_calculator.clone();
+            thread::spawn(move || {
+                let mut rewriter = Rewriter::with_shared_offset_calculator(&offset_calculator);
+                let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), replacement);
+                let _: String = rewriter.rewrite(&span((2, 4), (2, 5)), replacement);
+                rewriter.contents()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let contents = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        vec!["x + b\nc - x\n", "y + b\nc - y\n", "z + b\nc - z\n"],
+        contents
+    );
+}
+
+#[test]
+fn owned_rewriter() {
+    let offset_calculator = Arc::new(SharedOffsetCalculator::new("a + b\n"));
+    let mut rewriter = OwnedRewriter::with_shared_offset_calculator(offset_calculator.clone());
+    let handle = thread::spawn(move || {
+        let _: String = rewriter.rewrite(&span((1, 2), (1, 3)), "-");
+        rewriter.contents()
+    });
+    assert_eq!("a - b\n", handle.join().unwrap());
+    assert_eq!(
+        (0, 1),
+        offset_calculator.offsets_from_span(&span((1, 0), (1, 1)))
+    );
+}
+
+#[test]
+fn agrees_with_offset_calculator() {
+    let original = "αβ\n\nγ δ\nε";
+    let spans = [
+        span((1, 0), (1, 1)),
+        span((1, 1), (3, 2)),
+        span((2, 0), (2, 0)),
+        span((3, 3), (4, 1)),
+        span((4, 1), (4, 1)),
+    ];
+
+    let shared = SharedOffsetCalculator::new(original);
+    let mut offset_calculator = OffsetCalculator::new(original);
+    for span in &spans {
+        assert_eq!(
+            offset_calculator.offsets_from_span(span),
+            shared.offsets_from_span(span)
+        );
+    }
+}
+
+#[test]
+fn try_offsets_from_span() {
+    let offset_calculator = SharedOffsetCalculator::new("ab\nc");
+    assert_eq!(
+        Ok((3, 4)),
+        offset_calculator.try_offsets_from_span(&span((2, 0), (2, 1)))
+    );
+    assert_eq!(
+        Err(RewriteError::ColumnPastEndOfLine {
+            line_column: LineColumn { line: 1, column: 3 },
+            end_of_line: LineColumn { line: 1, column: 2 },
+        }),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 3)))
+    );
+    assert_eq!(
+        Err(RewriteError::PastEndOfFile {
+            line_column: LineColumn { line: 2, column: 2 },
+            end_of_file: LineColumn { line: 2, column: 1 },
+        }),
+        offset_calculator.try_offsets_from_span(&span((2, 0), (2, 2)))
+    );
+    assert_eq!(
+        Err(RewriteError::LineOutOfBounds {
+            line_column: LineColumn { line: 3, column: 0 },
+        }),
+        offset_calculator.try_offsets_from_span(&span((2, 0), (3, 0)))
+    );
+    // smoelius: Unlike an `OffsetCalculator`, a `SharedOffsetCalculator` can be queried out of
+    // order.
+    assert_eq!(
+        Ok((0, 2)),
+        offset_calculator.try_offsets_from_span(&span((1, 0), (1, 2)))
+    );
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use common::{line_column, span};
+use rewriter::{ColumnEncoding, Edit, LineTerminators, Rewriter};
+
+#[test]
+fn source_map() {
+    let mut rewriter = Rewriter::new("let x = 0;\nlet y = x;\n");
+    let _: String = rewriter.rewrite(&span((1, 4), (1, 5)), "long_name");
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 0)), "// é\n");
+    let _: String = rewriter.rewrite(&span((2, 8), (2, 9)), "long_name");
+
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("let long_name = 0;\n// é\nlet y = long_name;\n", contents);
+
+    assert_eq!(
+        &[
+            Edit {
+                original: 4..5,
+                rewritten: 4..13
+            },
+            Edit {
+                original: 11..11,
+                rewritten: 19..25
+            },
+            Edit {
+                original: 19..20,
+                rewritten: 33..42
+            },
+        ],
+        source_map.edits()
+    );
+    assert_eq!(
+        vec![
+            line_column(1, 4)..line_column(1, 13),
+            line_column(2, 0)..line_column(3, 0),
+            line_column(3, 8)..line_column(3, 17),
+        ],
+        source_map.replacements().collect::<Vec<_>>()
+    );
+
+    // smoelius: Unchanged text, the start and end of a replaced range, and a position inside one.
+    assert_eq!(Some(0), source_map.rewritten_offset(0));
+    assert_eq!(Some(4), source_map.rewritten_offset(4));
+    assert_eq!(Some(13), source_map.rewritten_offset(5));
+    assert_eq!(Some(19), source_map.rewritten_offset(11));
+    assert_eq!(Some(43), source_map.rewritten_offset(21));
+    assert_eq!(Some(44), source_map.rewritten_offset(22));
+    assert_eq!(None, source_map.rewritten_offset(23));
+
+    assert_eq!(Some(5), source_map.original_offset(13));
+    assert_eq!(None, source_map.original_offset(10));
+    assert_eq!(None, source_map.original_offset(22));
+    assert_eq!(Some(12), source_map.original_offset(26));
+
+    assert_eq!(
+        Some(line_column(3, 6)),
+        source_map.rewritten_line_column(line_column(2, 6))
+    );
+    assert_eq!(
+        Some(line_column(2, 6)),
+        source_map.original_line_column(line_column(3, 6))
+    );
+    assert_eq!(None, source_map.original_line_column(line_column(2, 4)));
+    assert_eq!(None, source_map.rewritten_line_column(line_column(1, 11)));
+}
+
+#[test]
+fn utf16() {
+    let mut rewriter = Rewriter::with_encoding("😀x = 1;\n", ColumnEncoding::Utf16);
+    let _: String = rewriter.rewrite(&span((1, 2), (1, 3)), "yy");
+
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("😀yy = 1;\n", contents);
+
+    assert_eq!(
+        vec![line_column(1, 2)..line_column(1, 4)],
+        source_map.replacements().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Some(line_column(1, 5)),
+        source_map.rewritten_line_column(line_column(1, 4))
+    );
+    assert_eq!(
+        Some(line_column(1, 4)),
+        source_map.original_line_column(line_column(1, 5))
+    );
+}
+
+#[test]
+fn crlf() {
+    let mut rewriter = Rewriter::with_format("a\r\nb\r\n", LineTerminators::LfCrlf.into());
+    let _: String = rewriter.rewrite(&span((2, 0), (2, 1)), "bb");
+
+    let (contents, source_map) = rewriter.contents_with_source_map();
+    assert_eq!("a\r\nbb\r\n", contents);
+
+    assert_eq!(
+        vec![line_column(2, 0)..line_column(2, 2)],
+        source_map.replacements().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        Some(line_column(3, 0)),
+        source_map.rewritten_line_column(line_column(3, 0))
+    );
+    // smoelius: The `\r` is part of the line terminator, not the line.
+    assert_eq!(None, source_map.rewritten_line_column(line_column(1, 2)));
+}
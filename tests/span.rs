@@ -0,0 +1,80 @@
+mod common;
+
+use common::{line_column, span};
+use rewriter::{
+    LineColumn, Span,
+    interface::{FromLineColumns, Span as _},
+};
+use std::ops::Range;
+
+#[test]
+fn predicates() {
+    let s = span((1, 2), (2, 1));
+    assert!(!s.is_empty());
+    assert!(span((1, 2), (1, 2)).is_empty());
+
+    assert!(s.contains(line_column(1, 2)));
+    assert!(s.contains(line_column(1, 9)));
+    assert!(!s.contains(line_column(2, 1)));
+    assert!(!span((1, 2), (1, 2)).contains(line_column(1, 2)));
+
+    assert!(s.contains_span(&span((1, 3), (2, 1))));
+    assert!(!s.contains_span(&span((1, 1), (1, 3))));
+
+    assert!(s.overlaps(&span((2, 0), (3, 0))));
+    assert!(!s.overlaps(&span((2, 1), (3, 0))));
+    assert!(!s.overlaps(&span((1, 2), (1, 2))));
+    assert!(s.overlaps(&span((1, 3), (1, 3))));
+}
+
+#[test]
+fn constructors() {
+    let s = span((1, 2), (2, 1));
+    assert_eq!(
+        Some(span((2, 0), (2, 1))),
+        s.intersect(&span((2, 0), (3, 0)))
+    );
+    assert_eq!(
+        Some(span((2, 1), (2, 1))),
+        s.intersect(&span((2, 1), (3, 0)))
+    );
+    assert_eq!(None, s.intersect(&span((2, 2), (3, 0))));
+    assert_eq!(span((1, 2), (3, 0)), s.cover(&span((2, 2), (3, 0))));
+    assert_eq!(span((1, 2), (1, 2)), s.shrink_to_start());
+    assert_eq!(span((2, 1), (2, 1)), s.shrink_to_end());
+    assert_eq!(span((2, 1), (2, 4)), s.between(&span((2, 4), (2, 5))));
+}
+
+#[test]
+#[should_panic = "LineColumn { line: 1, column: 0 } starts before LineColumn { line: 2, column: 1 }"]
+fn between_reversed() {
+    let _ = span((1, 2), (2, 1)).between(&span((1, 0), (1, 1)));
+}
+
+#[test]
+fn ranges() {
+    let s = span((1, 2), (2, 1));
+    let range: Range<LineColumn> = s.into();
+    assert_eq!(line_column(1, 2)..line_column(2, 1), range);
+    assert_eq!(range, s.to_range());
+    assert_eq!(s, Span::from(range.clone()));
+    assert_eq!(s, Span::from_range(range));
+}
+
+#[cfg(feature = "proc-macro2-impl")]
+#[test]
+fn proc_macro2() {
+    use proc_macro2::TokenStream;
+    use std::str::FromStr;
+
+    let tokens = TokenStream::from_str("fn\nfoo").unwrap();
+    let proc_macro2_span = tokens.into_iter().nth(1).unwrap().span();
+    let s = Span::from(proc_macro2_span);
+    assert_eq!(span((2, 0), (2, 3)), s);
+    let (start, end): (proc_macro2::LineColumn, proc_macro2::LineColumn) = s.into();
+    assert_eq!(
+        (proc_macro2_span.start(), proc_macro2_span.end()),
+        (start, end)
+    );
+    assert_eq!(line_column(2, 0), LineColumn::from(start));
+}
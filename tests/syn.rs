@@ -0,0 +1,71 @@
+#![cfg(feature = "syn")]
+
+use proc_macro2::Span;
+use rewriter::{OwnedRewriter, RewriteError, Rewriter};
+use syn::{Expr, File, Item, Stmt, parse_quote};
+
+const ORIGINAL: &str = "\
+fn f() {
+    let x = 1 + 2;
+}
+
+struct S;
+";
+
+#[test]
+fn rewrite_node() {
+    let file: File = syn::parse_str(ORIGINAL).unwrap();
+    let Item::Fn(item_fn) = &file.items[0] else {
+        panic!();
+    };
+    let Stmt::Local(local) = &item_fn.block.stmts[0] else {
+        panic!();
+    };
+    let expr = &local.init.as_ref().unwrap().expr;
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    assert_eq!("1 + 2", rewriter.rewrite_node(expr, "3"));
+    assert_eq!("struct S;", rewriter.delete_node(&file.items[1]));
+    assert_eq!("fn f() {\n    let x = 3;\n}\n\n\n", rewriter.contents());
+}
+
+#[test]
+fn replace_with_tokens() {
+    let file: File = syn::parse_str(ORIGINAL).unwrap();
+    let expr: Expr = parse_quote!(x * y);
+
+    let mut rewriter = OwnedRewriter::<Span>::new(ORIGINAL);
+    assert_eq!(
+        "struct S;",
+        rewriter.replace_with_tokens(&file.items[1], &expr)
+    );
+    assert_eq!(
+        "fn f() {\n    let x = 1 + 2;\n}\n\nx * y\n",
+        rewriter.contents()
+    );
+}
+
+#[test]
+fn try_rewrite_node() {
+    let file: File = syn::parse_str(ORIGINAL).unwrap();
+    let other: File = syn::parse_str("fn g(){}").unwrap();
+
+    let mut rewriter = Rewriter::<Span>::new(ORIGINAL);
+    let expr: Expr = parse_quote!(x);
+    assert!(matches!(
+        rewriter.try_rewrite_node(&expr, "y"),
+        Err(RewriteError::NoLocation)
+    ));
+    assert!(matches!(
+        rewriter.try_rewrite_node(&other, "y"),
+        Err(RewriteError::ForeignSpan { .. })
+    ));
+    assert!(matches!(
+        rewriter.try_rewrite_node(&syn::Generics::default(), "y"),
+        Err(RewriteError::NoLocation)
+    ));
+    assert_eq!(
+        Ok(String::from("struct S;")),
+        rewriter.try_rewrite_node(&file.items[1], "struct U;")
+    );
+}
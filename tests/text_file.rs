@@ -0,0 +1,99 @@
+mod common;
+
+use common::span;
+use rewriter::{FileEncoding, Rewriter, Span, TextFile};
+use std::{fs::read, io::ErrorKind};
+use tempfile::tempdir;
+
+fn rewrite_first_char(text_file: &TextFile, replacement: &str) -> String {
+    let mut rewriter = Rewriter::<Span>::new(text_file.contents());
+    let _: String = rewriter.rewrite(&span((1, 0), (1, 1)), replacement);
+    rewriter.contents()
+}
+
+#[test]
+fn utf8_bom() {
+    let tempdir = tempdir().unwrap();
+    let path = tempdir.path().join("file.txt");
+    std::fs::write(&path, b"\xEF\xBB\xBFab\n").unwrap();
+
+    let text_file = TextFile::read(&path).unwrap();
+    assert_eq!(FileEncoding::Utf8, text_file.encoding());
+    assert!(text_file.has_bom());
+    assert_eq!("ab\n", text_file.contents());
+
+    let contents = rewrite_first_char(&text_file, "x");
+    text_file.write(&path, &contents).unwrap();
+    assert_eq!(b"\xEF\xBB\xBFxb\n", read(&path).unwrap().as_slice());
+}
+
+#[test]
+fn utf8_without_bom() {
+    let text_file = TextFile::decode("αb".as_bytes()).unwrap();
+    assert!(!text_file.has_bom());
+    let contents = rewrite_first_char(&text_file, "x");
+    assert_eq!(b"xb", text_file.encode(&contents).unwrap().as_slice());
+}
+
+#[test]
+fn utf16_le() {
+    let text_file = TextFile::decode(b"\xFF\xFEa\x00\x34\xD8\x1E\xDD").unwrap();
+    assert_eq!(FileEncoding::Utf16Le, text_file.encoding());
+    assert!(text_file.has_bom());
+    assert_eq!("a𝄞", text_file.contents());
+    let contents = rewrite_first_char(&text_file, "é");
+    assert_eq!(
+        b"\xFF\xFE\xE9\x00\x34\xD8\x1E\xDD",
+        text_file.encode(&contents).unwrap().as_slice()
+    );
+}
+
+#[test]
+fn utf16_be() {
+    let text_file = TextFile::decode(b"\xFE\xFF\x00a\x00b").unwrap();
+    assert_eq!(FileEncoding::Utf16Be, text_file.encoding());
+    let contents = rewrite_first_char(&text_file, "x");
+    assert_eq!(
+        b"\xFE\xFF\x00x\x00b",
+        text_file.encode(&contents).unwrap().as_slice()
+    );
+}
+
+#[test]
+fn utf16_without_bom() {
+    let text_file = TextFile::decode_with_encoding(b"a\x00b\x00", FileEncoding::Utf16Le).unwrap();
+    assert!(!text_file.has_bom());
+    assert_eq!("ab", text_file.contents());
+    assert_eq!(b"a\x00b\x00", text_file.encode("ab").unwrap().as_slice());
+}
+
+#[test]
+fn latin1() {
+    let text_file = TextFile::decode_with_encoding(b"\xE9t\xE9", FileEncoding::Latin1).unwrap();
+    assert_eq!("été", text_file.contents());
+    let contents = rewrite_first_char(&text_file, "\u{E8}");
+    assert_eq!(
+        b"\xE8t\xE9",
+        text_file.encode(&contents).unwrap().as_slice()
+    );
+    assert_eq!(
+        ErrorKind::InvalidData,
+        text_file.encode("€").unwrap_err().kind()
+    );
+}
+
+#[test]
+fn invalid_data() {
+    assert_eq!(
+        ErrorKind::InvalidData,
+        TextFile::decode(b"\xE9t\xE9").unwrap_err().kind()
+    );
+    assert_eq!(
+        ErrorKind::InvalidData,
+        TextFile::decode(b"\xFF\xFEa").unwrap_err().kind()
+    );
+    assert_eq!(
+        ErrorKind::InvalidData,
+        TextFile::decode(b"\xFF\xFE\x3B\xD8").unwrap_err().kind()
+    );
+}
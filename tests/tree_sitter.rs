@@ -0,0 +1,77 @@
+#![cfg(feature = "tree-sitter")]
+
+use rewriter::{
+    ColumnEncoding, LineTerminators, OffsetCalculator, Rewriter, TextFormat,
+    interface::{LineColumn as _, Span as _},
+};
+use tree_sitter::{Node, Parser, Point, Range, Tree};
+
+const ORIGINAL: &str = "{\n  \"é\": \"ü\",\n  \"x\": [1, 2]\n}\n";
+
+fn parse(text: &str) -> Tree {
+    let mut parser = Parser::new();
+    parser
+        .set_language(&tree_sitter_json::LANGUAGE.into())
+        .unwrap();
+    parser.parse(text, None).unwrap()
+}
+
+fn strings(node: Node<'_>, ranges: &mut Vec<Range>) {
+    if node.kind() == "string" {
+        ranges.push(node.range());
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        strings(child, ranges);
+    }
+}
+
+fn rewrite_strings(mut rewriter: Rewriter<'_, Range>, ranges: &[Range]) -> String {
+    for range in ranges {
+        let replaced = rewriter.rewrite(range, "\"s\"");
+        assert_eq!(&ORIGINAL[range.start_byte..range.end_byte], replaced);
+    }
+    rewriter.contents()
+}
+
+#[test]
+fn tree_sitter() {
+    let tree = parse(ORIGINAL);
+    let mut ranges = Vec::new();
+    strings(tree.root_node(), &mut ranges);
+    assert_eq!(3, ranges.len());
+
+    // smoelius: Tree-sitter columns are bytes, so the second string starts at column 8, even though
+    // it is preceded by only seven characters. The row is 1-based, unlike
+    // `ranges[1].start_point`'s.
+    assert_eq!(Point { row: 1, column: 8 }, ranges[1].start_point);
+    assert_eq!(Point { row: 2, column: 8 }, ranges[1].start());
+    assert_eq!(2, ranges[1].start().line());
+
+    let expected = "{\n  \"s\": \"s\",\n  \"s\": [1, 2]\n}\n";
+    assert_eq!(
+        expected,
+        rewrite_strings(Rewriter::<Range>::new(ORIGINAL), &ranges)
+    );
+
+    // smoelius: With a non-default format, the ranges' rows and columns are resolved rather than
+    // their byte offsets used.
+    let format = TextFormat {
+        column_encoding: ColumnEncoding::Bytes,
+        line_terminators: LineTerminators::LfCrlf,
+    };
+    assert_eq!(
+        expected,
+        rewrite_strings(Rewriter::<Range>::with_format(ORIGINAL, format), &ranges)
+    );
+}
+
+#[test]
+fn line_column_from_offset() {
+    let offset_calculator = OffsetCalculator::<Range>::new(ORIGINAL);
+    assert_eq!(
+        Point { row: 2, column: 8 },
+        offset_calculator.line_column_from_offset(10)
+    );
+}
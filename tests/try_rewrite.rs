@@ -0,0 +1,101 @@
+mod common;
+
+use common::{line_column, span};
+use rewriter::{OffsetCalculator, RewriteError, Rewriter};
+
+// smoelius:
+//
+// (1,0) a b (1,2)
+// (2,0) c (2,1)
+
+#[test]
+fn errors() {
+    const ORIGINAL: &str = "ab\nc";
+
+    let mut rewriter = Rewriter::new(ORIGINAL);
+
+    assert_eq!(
+        Err(RewriteError::ReversedSpan {
+            start: line_column(1, 2),
+            end: line_column(1, 1),
+        }),
+        rewriter.try_rewrite(&span((1, 2), (1, 1)), "")
+    );
+    assert_eq!(
+        Err(RewriteError::ColumnPastEndOfLine {
+            line_column: line_column(1, 3),
+            end_of_line: line_column(1, 2),
+        }),
+        rewriter.try_rewrite(&span((1, 1), (1, 3)), "")
+    );
+    assert_eq!(
+        Err(RewriteError::PastEndOfFile {
+            line_column: line_column(2, 2),
+            end_of_file: line_column(2, 1),
+        }),
+        rewriter.try_rewrite(&span((2, 0), (2, 2)), "")
+    );
+    assert_eq!(
+        Err(RewriteError::LineOutOfBounds {
+            line_column: line_column(3, 0),
+        }),
+        rewriter.try_rewrite(&span((2, 0), (3, 0)), "")
+    );
+
+    assert_eq!(
+        Ok(String::from("b")),
+        rewriter.try_rewrite(&span((1, 1), (1, 2)), "B")
+    );
+    assert_eq!(
+        Err(RewriteError::OutOfOrder {
+            previous_end: line_column(1, 2),
+            start: line_column(1, 0),
+        }),
+        rewriter.try_rewrite(&span((1, 0), (1, 1)), "")
+    );
+    assert_eq!(
+        Ok(String::new()),
+        rewriter.try_rewrite(&span((2, 1), (2, 1)), "d")
+    );
+
+    assert_eq!("aB\ncd", rewriter.contents());
+}
+
+#[test]
+fn line_zero() {
+    let mut offset_calculator = OffsetCalculator::new("ab\nc");
+
+    assert_eq!(
+        Err(RewriteError::LineOutOfBounds {
+            line_column: line_column(0, 0),
+        }),
+        offset_calculator.try_offsets_from_span(&span((0, 0), (1, 0)))
+    );
+    assert_eq!(
+        Ok((1, 4)),
+        offset_calculator.try_offsets_from_span(&span((1, 1), (2, 1)))
+    );
+}
+
+#[test]
+fn error_does_not_advance() {
+    let mut rewriter = Rewriter::new("ab\ncd\nef");
+
+    assert!(rewriter.try_rewrite(&span((1, 0), (2, 5)), "").is_err());
+    assert_eq!(
+        Ok(String::from("b\nc")),
+        rewriter.try_rewrite(&span((1, 1), (2, 1)), "")
+    );
+    assert_eq!("ad\nef", rewriter.contents());
+}
+
+#[test]
+fn end_of_file_insertion() {
+    let mut rewriter = Rewriter::new("ab\nc");
+
+    assert_eq!(
+        String::new(),
+        rewriter.rewrite(&span((2, 1), (2, 1)), "d\n")
+    );
+    assert_eq!("ab\ncd\n", rewriter.contents());
+}